@@ -1,10 +1,44 @@
-#[derive(Debug, Eq, PartialEq)]
+use std::fmt::{self, Display, Formatter};
+
+use crate::error::Diagnostic;
+
+/// Names that the generated code relies on, which structs must not shadow.
+#[rustfmt::skip]
+const RESERVED_NAMES: &[&str] = &[
+    // Rust prelude types and traits.
+    "Box", "Clone", "Copy", "Default", "Drop", "Eq", "Err", "Fn", "FnMut", "FnOnce", "From",
+    "Into", "Iterator", "None", "Ok", "Option", "Ord", "PartialEq", "PartialOrd", "Result",
+    "Send", "Sized", "Some", "String", "Sync", "ToOwned", "ToString", "Vec",
+];
+
+/// A byte range of the schema source.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// Returns the 1-based line and column of the start of the span in `source`.
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let before = source.get(..self.start).unwrap_or(source);
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FieldType {
     Bool,
     U8,
     U16,
     U32,
     U64,
+    I8,
+    I16,
     I32,
     I64,
     F32,
@@ -12,7 +46,18 @@ pub enum FieldType {
     Timestamp,
     Text,
     Binary,
+    Opaque8,
+    Opaque16,
+    Opaque32,
+    Opaque64,
     Struct(String),
+    ArrayU16,
+    ArrayU32,
+    ArrayU64,
+    ArrayI8,
+    ArrayI16,
+    ArrayI32,
+    ArrayI64,
     ArrayF32,
     ArrayF64,
     ArrayText,
@@ -20,30 +65,101 @@ pub enum FieldType {
     ArrayStruct(String),
 }
 
+impl Display for FieldType {
+    /// Formats the type as written in schemas.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldType::Bool => "bool",
+            FieldType::U8 => "uint8",
+            FieldType::U16 => "uint16",
+            FieldType::U32 => "uint32",
+            FieldType::U64 => "uint64",
+            FieldType::I8 => "int8",
+            FieldType::I16 => "int16",
+            FieldType::I32 => "int32",
+            FieldType::I64 => "int64",
+            FieldType::F32 => "float32",
+            FieldType::F64 => "float64",
+            FieldType::Timestamp => "timestamp",
+            FieldType::Text => "text",
+            FieldType::Binary => "binary",
+            FieldType::Opaque8 => "opaque8",
+            FieldType::Opaque16 => "opaque16",
+            FieldType::Opaque32 => "opaque32",
+            FieldType::Opaque64 => "opaque64",
+            FieldType::Struct(name) => name,
+            FieldType::ArrayU16 => "[]uint16",
+            FieldType::ArrayU32 => "[]uint32",
+            FieldType::ArrayU64 => "[]uint64",
+            FieldType::ArrayI8 => "[]int8",
+            FieldType::ArrayI16 => "[]int16",
+            FieldType::ArrayI32 => "[]int32",
+            FieldType::ArrayI64 => "[]int64",
+            FieldType::ArrayF32 => "[]float32",
+            FieldType::ArrayF64 => "[]float64",
+            FieldType::ArrayText => "[]text",
+            FieldType::ArrayBinary => "[]binary",
+            FieldType::ArrayStruct(name) => return write!(f, "[]{}", name),
+        };
+        f.write_str(name)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct Field {
     pub name: String,
+    pub schema_name: String,
     pub ty: FieldType,
+    /// The span of the name.
+    pub span: Span,
+    /// The span of the type.
+    pub ty_span: Span,
+    /// The lines of the comments preceding the field.
+    pub docs: Vec<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Struct {
     pub name: String,
     pub fields: Vec<Field>,
+    /// The span of the name.
+    pub span: Span,
+    /// The lines of the comments preceding the struct.
+    pub docs: Vec<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Colfer {
     pub package: String,
     pub structs: Vec<Struct>,
+    /// The span of the package name.
+    pub package_span: Span,
 }
 
 impl Colfer {
+    /// Returns the struct named `name`.
+    pub fn find_struct(&self, name: &str) -> Option<&Struct> {
+        self.structs.iter().find(|s| s.name == name)
+    }
+
+    /// Returns `true` if a field of struct type `ty` inside struct `start` must be boxed,
+    /// because `ty` contains `start` again.
     pub fn need_box(&self, start: &str, ty: &str) -> bool {
-        if let Some(s) = self.structs.iter().find(|s| s.name == start) {
+        self.contains(ty, start, &mut Vec::new())
+    }
+
+    fn contains<'a>(&'a self, outer: &'a str, inner: &str, visited: &mut Vec<&'a str>) -> bool {
+        if outer == inner {
+            return true;
+        }
+        if visited.contains(&outer) {
+            return false;
+        }
+        visited.push(outer);
+        if let Some(s) = self.find_struct(outer) {
             for field in &s.fields {
                 if let FieldType::Struct(struct_name) = &field.ty {
-                    if struct_name == start || self.need_box(&struct_name, ty) {
+                    if self.contains(struct_name, inner, visited) {
                         return true;
                     }
                 }
@@ -52,31 +168,366 @@ impl Colfer {
         false
     }
 
-    pub fn validate(&self) -> anyhow::Result<()> {
-        self.validate_field_types()?;
-        self.validate_fields_count()?;
-        Ok(())
+    /// Checks the schema for anything that would make the generated code invalid, returning
+    /// all problems that were found.
+    pub fn validate(&self) -> Result<(), Vec<Diagnostic>> {
+        self.validate_with(&[])
     }
 
-    fn validate_fields_count(&self) -> anyhow::Result<()> {
-        for s in &self.structs {
+    /// Like [`Colfer::validate`], for a schema file that is compiled along with the files
+    /// `others`, whose structs it may reference.
+    pub fn validate_with(&self, others: &[&Colfer]) -> Result<(), Vec<Diagnostic>> {
+        let is_defined = |package: &str, name: &str| {
+            std::iter::once(self)
+                .chain(others.iter().copied())
+                .any(|file| file.package == package && file.find_struct(name).is_some())
+        };
+
+        let mut diagnostics = Vec::new();
+        if self.structs.is_empty() {
+            diagnostics.push(Diagnostic::new(
+                self.package_span,
+                format!("package `{}` does not define any structs", self.package),
+            ));
+        }
+
+        for (idx, s) in self.structs.iter().enumerate() {
+            if self.structs[..idx].iter().any(|other| other.name == s.name) {
+                diagnostics.push(Diagnostic::new(
+                    s.span,
+                    format!("struct `{}` is defined more than once", s.name),
+                ));
+            }
+            if others
+                .iter()
+                .any(|other| other.package == self.package && other.find_struct(&s.name).is_some())
+            {
+                diagnostics.push(Diagnostic::new(
+                    s.span,
+                    format!(
+                        "struct `{}` is also defined by another file of package `{}`",
+                        s.name, self.package
+                    ),
+                ));
+            }
+            if RESERVED_NAMES.contains(&s.name.as_str()) {
+                diagnostics.push(Diagnostic::new(
+                    s.span,
+                    format!(
+                        "struct `{}` clashes with a type used by the generated code",
+                        s.name
+                    ),
+                ));
+            }
             if s.fields.len() > 127 {
-                anyhow::anyhow!("The maximum number of fields in a struct must be less than 128, but struct `{}` exceeds this limit.", s.name);
+                diagnostics.push(Diagnostic::new(
+                    s.span,
+                    format!(
+                        "struct `{}` has {} fields, but at most 127 are supported",
+                        s.name,
+                        s.fields.len()
+                    ),
+                ));
             }
+
+            for (idx, f) in s.fields.iter().enumerate() {
+                if let Some(other) = s.fields[..idx].iter().find(|other| other.name == f.name) {
+                    let message = if other.schema_name == f.schema_name {
+                        format!(
+                            "field `{}` of struct `{}` is defined more than once",
+                            f.schema_name, s.name
+                        )
+                    } else {
+                        format!(
+                            "fields `{}` and `{}` of struct `{}` are both named `{}` in Rust",
+                            other.schema_name, f.schema_name, s.name, f.name
+                        )
+                    };
+                    diagnostics.push(Diagnostic::new(f.span, message));
+                }
+                if let FieldType::Struct(name) | FieldType::ArrayStruct(name) = &f.ty {
+                    let (package, struct_name) = split_reference(&self.package, name);
+                    if !is_defined(package, struct_name) {
+                        diagnostics.push(Diagnostic::new(
+                            f.ty_span,
+                            format!("struct `{}` is not defined", name),
+                        )
+                        .with_help(
+                            "field types are `bool`, `uint8`, `uint16`, `uint32`, `uint64`, \
+                             `int8`, `int16`, `int32`, `int64`, `float32`, `float64`, \
+                             `timestamp`, `text`, `binary`, `opaque8`, `opaque16`, `opaque32`, \
+                             `opaque64` and the structs of the package",
+                        ));
+                    }
+                }
+            }
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(diagnostics)
         }
-        Ok(())
     }
+}
 
-    fn validate_field_types(&self) -> anyhow::Result<()> {
-        for s in &self.structs {
-            for f in &s.fields {
-                if let FieldType::Struct(name) | FieldType::ArrayStruct(name) = &f.ty {
-                    if self.structs.iter().find(|s| &s.name == name).is_none() {
-                        anyhow::anyhow!("Struct `{}` is not defined.", name);
+/// Splits a reference to a struct from `package` into the package and the name of the struct.
+fn split_reference<'a>(package: &'a str, reference: &'a str) -> (&'a str, &'a str) {
+    match reference.find('.') {
+        Some(idx) => (&reference[..idx], &reference[idx + 1..]),
+        None => (package, reference),
+    }
+}
+
+/// The packages of schema files which are compiled together. Structs reference the structs of
+/// other packages as `package.Name`.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Schema {
+    pub packages: Vec<Colfer>,
+}
+
+impl Schema {
+    /// Merges the structs of `files` by package, in the order of the first file of each
+    /// package. References qualified with their own package are made unqualified.
+    pub fn new(files: Vec<Colfer>) -> Self {
+        let mut packages: Vec<Colfer> = Vec::new();
+        for mut file in files {
+            let prefix = format!("{}.", file.package);
+            for s in &mut file.structs {
+                for f in &mut s.fields {
+                    if let FieldType::Struct(name) | FieldType::ArrayStruct(name) = &mut f.ty {
+                        if name.starts_with(&prefix) {
+                            name.drain(..prefix.len());
+                        }
                     }
                 }
             }
+            match packages.iter_mut().find(|p| p.package == file.package) {
+                Some(package) => package.structs.append(&mut file.structs),
+                None => packages.push(file),
+            }
+        }
+        Self { packages }
+    }
+
+    /// Returns the package named `name`.
+    pub fn package(&self, name: &str) -> Option<&Colfer> {
+        self.packages.iter().find(|p| p.package == name)
+    }
+
+    /// Returns the struct `name`, along with its package. The name may omit the package if
+    /// only one package defines a struct of that name.
+    pub fn find_struct(&self, name: &str) -> Option<(&Colfer, &Struct)> {
+        if name.contains('.') {
+            return self.resolve("", name);
+        }
+        let mut found = self
+            .packages
+            .iter()
+            .filter_map(|colfer| Some((colfer, colfer.find_struct(name)?)));
+        match (found.next(), found.next()) {
+            (Some(found), None) => Some(found),
+            _ => None,
+        }
+    }
+
+    /// Returns the struct referenced as `reference` from `package`, along with its package.
+    pub fn resolve(&self, package: &str, reference: &str) -> Option<(&Colfer, &Struct)> {
+        let (package, name) = split_reference(package, reference);
+        let colfer = self.package(package)?;
+        Some((colfer, colfer.find_struct(name)?))
+    }
+
+    /// Returns `true` if a field of struct type `ty` inside struct `start` of `package` must
+    /// be boxed, because `ty` contains `start` again, possibly through other packages.
+    pub fn need_box(&self, package: &str, start: &str, ty: &str) -> bool {
+        self.contains(package, ty, (package, start), &mut Vec::new())
+    }
+
+    /// Returns `true` if struct `name` of `package` has a floating point field, directly or
+    /// through nested structs.
+    pub fn has_float(&self, package: &str, name: &str) -> bool {
+        self.find_float(package, name, &mut Vec::new())
+    }
+
+    fn find_float<'a>(
+        &'a self,
+        package: &str,
+        name: &str,
+        visited: &mut Vec<(&'a str, &'a str)>,
+    ) -> bool {
+        let (colfer, s) = match self.resolve(package, name) {
+            Some(found) => found,
+            None => return false,
+        };
+        let key = (colfer.package.as_str(), s.name.as_str());
+        if visited.contains(&key) {
+            return false;
+        }
+        visited.push(key);
+        s.fields.iter().any(|field| match &field.ty {
+            FieldType::F32 | FieldType::F64 | FieldType::ArrayF32 | FieldType::ArrayF64 => true,
+            FieldType::Struct(name) | FieldType::ArrayStruct(name) => {
+                self.find_float(&colfer.package, name, visited)
+            }
+            _ => false,
+        })
+    }
+
+    fn contains<'a>(
+        &'a self,
+        package: &str,
+        outer: &str,
+        inner: (&str, &str),
+        visited: &mut Vec<(&'a str, &'a str)>,
+    ) -> bool {
+        let (colfer, s) = match self.resolve(package, outer) {
+            Some(found) => found,
+            None => return false,
+        };
+        let key = (colfer.package.as_str(), s.name.as_str());
+        if key == inner {
+            return true;
+        }
+        if visited.contains(&key) {
+            return false;
         }
-        Ok(())
+        visited.push(key);
+        s.fields.iter().any(|field| match &field.ty {
+            FieldType::Struct(name) => self.contains(&colfer.package, name, inner, visited),
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_schema;
+
+    /// Returns the problems in `source` as `line:column: message`.
+    fn diagnostics(source: &str) -> Vec<String> {
+        parse_schema(source)
+            .unwrap_err()
+            .diagnostics()
+            .map(|(_, d)| {
+                let (line, column) = d.span.line_column(source);
+                format!("{}:{}: {}", line, column, d.message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_validate() {
+        let source = r#"package demo
+
+type a struct {
+	b b
+	c []c
+	name text
+	Name text
+	first_name text
+	firstName text
+}
+
+type b struct {
+	x bool
+	x bool
+}
+
+type B struct {
+	x bool
+}
+
+type option struct {
+	x bool
+}
+"#;
+        assert_eq!(
+            diagnostics(source),
+            vec![
+                "5:4: struct `C` is not defined",
+                "7:2: fields `name` and `Name` of struct `A` are both named `name` in Rust",
+                "9:2: fields `first_name` and `firstName` of struct `A` are both named `first_name` in Rust",
+                "14:2: field `x` of struct `B` is defined more than once",
+                "17:6: struct `B` is defined more than once",
+                "21:6: struct `Option` clashes with a type used by the generated code",
+            ]
+        );
+
+        assert_eq!(
+            diagnostics(
+                "package empty
+"
+            ),
+            vec!["1:9: package `empty` does not define any structs"]
+        );
+
+        let fields: String = (0..128).map(|idx| format!("\tf{} bool\n", idx)).collect();
+        assert_eq!(
+            diagnostics(&format!(
+                "package big
+type big struct {{
+{}}}
+",
+                fields
+            )),
+            vec!["2:6: struct `Big` has 128 fields, but at most 127 are supported"]
+        );
+    }
+
+    #[test]
+    fn test_schema() {
+        let parse = |source| crate::parser::parse(source).unwrap();
+        let order = parse(
+            "package shop
+type order struct {
+	buyer people.person
+	items []item
+}
+",
+        );
+        let item = parse(
+            "package shop
+type item struct {
+	order shop.order
+}
+",
+        );
+        let person = parse(
+            "package people
+type person struct {
+	last shop.order
+	x other.y
+}
+",
+        );
+
+        assert!(order.validate().is_err());
+        assert!(order.validate_with(&[&item, &person]).is_ok());
+        let messages: Vec<_> = person
+            .validate_with(&[&order, &item])
+            .unwrap_err()
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(messages, vec!["struct `other.Y` is not defined"]);
+        assert_eq!(
+            item.validate_with(&[&item]).unwrap_err()[0].message,
+            "struct `Item` is also defined by another file of package `shop`"
+        );
+
+        let schema = super::Schema::new(vec![order, item, person]);
+        let names: Vec<_> = schema.packages.iter().map(|p| &p.package).collect();
+        assert_eq!(names, vec!["shop", "people"]);
+        let shop = schema.package("shop").unwrap();
+        assert_eq!(shop.structs.len(), 2);
+        assert_eq!(
+            shop.structs[1].fields[0].ty,
+            super::FieldType::Struct("Order".to_string())
+        );
+
+        assert!(schema.need_box("shop", "Order", "people.Person"));
+        assert!(schema.need_box("people", "Person", "shop.Order"));
+        assert!(!schema.need_box("shop", "Item", "Order"));
     }
 }
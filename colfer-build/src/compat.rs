@@ -0,0 +1,501 @@
+//! Compatibility checks between two versions of a schema.
+//!
+//! Colfer identifies fields by their position in the struct, so peers with different versions
+//! of a schema can only exchange messages if the new version appends fields and keeps the
+//! existing ones in place with the same types.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::ast::{Colfer, Field, FieldType, Schema, Struct};
+
+/// A change between two versions of a schema.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Change {
+    PackageAdded {
+        name: String,
+    },
+    PackageRemoved {
+        name: String,
+    },
+    PackageRenamed {
+        old: String,
+        new: String,
+    },
+    StructAdded {
+        name: String,
+    },
+    StructRemoved {
+        name: String,
+    },
+    StructRenamed {
+        old: String,
+        new: String,
+    },
+    /// A field was added after the existing fields of a struct.
+    FieldAppended {
+        struct_name: String,
+        field: String,
+    },
+    /// A field was added before existing fields of a struct, which shifts their positions.
+    FieldInserted {
+        struct_name: String,
+        field: String,
+        index: usize,
+    },
+    FieldRemoved {
+        struct_name: String,
+        field: String,
+    },
+    /// A field was renamed in place, keeping its type.
+    FieldRenamed {
+        struct_name: String,
+        old: String,
+        new: String,
+    },
+    FieldMoved {
+        struct_name: String,
+        field: String,
+        old_index: usize,
+        new_index: usize,
+    },
+    FieldRetyped {
+        struct_name: String,
+        field: String,
+        old: FieldType,
+        new: FieldType,
+    },
+}
+
+impl Change {
+    /// Returns `true` if peers with the old and the new version of the schema can no longer
+    /// exchange messages, or if code referencing the schema by name breaks.
+    ///
+    /// Only adding packages and structs and appending fields are compatible. Renaming a
+    /// struct or a field keeps the wire format, but breaks the generated code that uses it.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(
+            self,
+            Change::PackageAdded { .. } | Change::StructAdded { .. } | Change::FieldAppended { .. }
+        )
+    }
+
+    /// Qualifies the struct names of the change with `package`.
+    fn qualify(self, package: &str) -> Self {
+        let q = |name: String| format!("{}.{}", package, name);
+        match self {
+            Change::StructAdded { name } => Change::StructAdded { name: q(name) },
+            Change::StructRemoved { name } => Change::StructRemoved { name: q(name) },
+            Change::StructRenamed { old, new } => Change::StructRenamed {
+                old: q(old),
+                new: q(new),
+            },
+            Change::FieldAppended { struct_name, field } => Change::FieldAppended {
+                struct_name: q(struct_name),
+                field,
+            },
+            Change::FieldInserted {
+                struct_name,
+                field,
+                index,
+            } => Change::FieldInserted {
+                struct_name: q(struct_name),
+                field,
+                index,
+            },
+            Change::FieldRemoved { struct_name, field } => Change::FieldRemoved {
+                struct_name: q(struct_name),
+                field,
+            },
+            Change::FieldRenamed {
+                struct_name,
+                old,
+                new,
+            } => Change::FieldRenamed {
+                struct_name: q(struct_name),
+                old,
+                new,
+            },
+            Change::FieldMoved {
+                struct_name,
+                field,
+                old_index,
+                new_index,
+            } => Change::FieldMoved {
+                struct_name: q(struct_name),
+                field,
+                old_index,
+                new_index,
+            },
+            Change::FieldRetyped {
+                struct_name,
+                field,
+                old,
+                new,
+            } => Change::FieldRetyped {
+                struct_name: q(struct_name),
+                field,
+                old,
+                new,
+            },
+            change => change,
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::PackageAdded { name } => write!(f, "package `{}` was added", name),
+            Change::PackageRemoved { name } => write!(f, "package `{}` was removed", name),
+            Change::PackageRenamed { old, new } => {
+                write!(f, "package `{}` was renamed to `{}`", old, new)
+            }
+            Change::StructAdded { name } => write!(f, "struct `{}` was added", name),
+            Change::StructRemoved { name } => write!(f, "struct `{}` was removed", name),
+            Change::StructRenamed { old, new } => {
+                write!(f, "struct `{}` was renamed to `{}`", old, new)
+            }
+            Change::FieldAppended { struct_name, field } => {
+                write!(f, "field `{}.{}` was appended", struct_name, field)
+            }
+            Change::FieldInserted {
+                struct_name,
+                field,
+                index,
+            } => write!(
+                f,
+                "field `{}.{}` was inserted at position {} instead of appended",
+                struct_name, field, index
+            ),
+            Change::FieldRemoved { struct_name, field } => {
+                write!(f, "field `{}.{}` was removed", struct_name, field)
+            }
+            Change::FieldRenamed {
+                struct_name,
+                old,
+                new,
+            } => write!(
+                f,
+                "field `{}.{}` was renamed to `{}`",
+                struct_name, old, new
+            ),
+            Change::FieldMoved {
+                struct_name,
+                field,
+                old_index,
+                new_index,
+            } => write!(
+                f,
+                "field `{}.{}` was moved from position {} to {}",
+                struct_name, field, old_index, new_index
+            ),
+            Change::FieldRetyped {
+                struct_name,
+                field,
+                old,
+                new,
+            } => write!(
+                f,
+                "field `{}.{}` changed type from `{}` to `{}`",
+                struct_name, field, old, new
+            ),
+        }
+    }
+}
+
+/// Returns the changes from schema `old` to schema `new`, struct by struct in the order of
+/// `old`, followed by the added structs.
+///
+/// A struct is considered renamed if it was removed and an added struct has the same fields.
+pub fn diff(old: &Colfer, new: &Colfer) -> Vec<Change> {
+    let mut changes = Vec::new();
+    if old.package != new.package {
+        changes.push(Change::PackageRenamed {
+            old: old.package.clone(),
+            new: new.package.clone(),
+        });
+    }
+
+    let added: Vec<_> = new
+        .structs
+        .iter()
+        .filter(|s| old.find_struct(&s.name).is_none())
+        .collect();
+    let mut renames = Vec::new();
+    for s in &old.structs {
+        if new.find_struct(&s.name).is_some() {
+            continue;
+        }
+        let renamed = added.iter().find(|a| {
+            !renames.iter().any(|(_, new)| new == &a.name) && same_fields(s, a, &renames)
+        });
+        if let Some(a) = renamed {
+            renames.push((s.name.clone(), a.name.clone()));
+        }
+    }
+
+    for s in &old.structs {
+        match new.find_struct(&s.name) {
+            Some(n) => diff_fields(s, n, &renames, &mut changes),
+            None => match renames.iter().find(|(old, _)| old == &s.name) {
+                Some((old, new)) => changes.push(Change::StructRenamed {
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                None => changes.push(Change::StructRemoved {
+                    name: s.name.clone(),
+                }),
+            },
+        }
+    }
+    for a in added {
+        if !renames.iter().any(|(_, new)| new == &a.name) {
+            changes.push(Change::StructAdded {
+                name: a.name.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Returns the changes from schema `old` to schema `new`, which may span several packages.
+///
+/// Schemas of a single package are compared like [`diff`] does. Otherwise packages are matched
+/// by name, and the struct names of the changes are qualified with their package.
+pub fn diff_schemas(old: &Schema, new: &Schema) -> Vec<Change> {
+    if let ([old], [new]) = (&old.packages[..], &new.packages[..]) {
+        return diff(old, new);
+    }
+
+    let mut changes = Vec::new();
+    for o in &old.packages {
+        match new.package(&o.package) {
+            Some(n) => changes.extend(diff(o, n).into_iter().map(|c| c.qualify(&o.package))),
+            None => changes.push(Change::PackageRemoved {
+                name: o.package.clone(),
+            }),
+        }
+    }
+    for n in &new.packages {
+        if old.package(&n.package).is_none() {
+            changes.push(Change::PackageAdded {
+                name: n.package.clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn diff_fields(
+    old: &Struct,
+    new: &Struct,
+    renames: &[(String, String)],
+    changes: &mut Vec<Change>,
+) {
+    let index = |fields: &[Field], name: &str| fields.iter().position(|f| f.schema_name == name);
+    let mut renamed = Vec::new();
+
+    for (i, f) in old.fields.iter().enumerate() {
+        match index(&new.fields, &f.schema_name) {
+            Some(j) if j != i => changes.push(Change::FieldMoved {
+                struct_name: new.name.clone(),
+                field: f.schema_name.clone(),
+                old_index: i,
+                new_index: j,
+            }),
+            Some(_) => {
+                if !same_type(&f.ty, &new.fields[i].ty, renames) {
+                    changes.push(Change::FieldRetyped {
+                        struct_name: new.name.clone(),
+                        field: f.schema_name.clone(),
+                        old: f.ty.clone(),
+                        new: new.fields[i].ty.clone(),
+                    });
+                }
+            }
+            None => match new.fields.get(i) {
+                Some(n)
+                    if index(&old.fields, &n.schema_name).is_none()
+                        && same_type(&f.ty, &n.ty, renames) =>
+                {
+                    renamed.push(i);
+                    changes.push(Change::FieldRenamed {
+                        struct_name: new.name.clone(),
+                        old: f.schema_name.clone(),
+                        new: n.schema_name.clone(),
+                    });
+                }
+                _ => changes.push(Change::FieldRemoved {
+                    struct_name: new.name.clone(),
+                    field: f.schema_name.clone(),
+                }),
+            },
+        }
+    }
+
+    for (j, n) in new.fields.iter().enumerate() {
+        if index(&old.fields, &n.schema_name).is_some() || renamed.contains(&j) {
+            continue;
+        }
+        if j >= old.fields.len() {
+            changes.push(Change::FieldAppended {
+                struct_name: new.name.clone(),
+                field: n.schema_name.clone(),
+            });
+        } else {
+            changes.push(Change::FieldInserted {
+                struct_name: new.name.clone(),
+                field: n.schema_name.clone(),
+                index: j,
+            });
+        }
+    }
+}
+
+/// Returns `true` if struct `old` has the same fields as struct `new`, assuming that it's
+/// renamed to `new` as well.
+fn same_fields(old: &Struct, new: &Struct, renames: &[(String, String)]) -> bool {
+    let mut renames = renames.to_vec();
+    renames.push((old.name.clone(), new.name.clone()));
+    old.fields.len() == new.fields.len()
+        && old
+            .fields
+            .iter()
+            .zip(&new.fields)
+            .all(|(f, n)| f.schema_name == n.schema_name && same_type(&f.ty, &n.ty, &renames))
+}
+
+fn same_type(old: &FieldType, new: &FieldType, renames: &[(String, String)]) -> bool {
+    let renamed = |name: &str| {
+        renames
+            .iter()
+            .find(|(old, _)| old == name)
+            .map_or(name.to_string(), |(_, new)| new.clone())
+    };
+    match (old, new) {
+        (FieldType::Struct(old), FieldType::Struct(new))
+        | (FieldType::ArrayStruct(old), FieldType::ArrayStruct(new)) => &renamed(old) == new,
+        _ => old == new,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    #[test]
+    fn test_diff() {
+        let old = parse_schema(
+            "package demo
+type a struct {
+	b bool
+	c uint32
+	d text
+	e binary
+	f a
+}
+type g struct {
+	h g
+}
+type i struct {
+	j bool
+}
+",
+        )
+        .unwrap();
+        let new = parse_schema(
+            "package demo
+type a struct {
+	b bool
+	c uint64
+	e binary
+	d text
+	ff a
+	k bool
+}
+type gg struct {
+	h gg
+}
+type l struct {
+	m bool
+}
+",
+        )
+        .unwrap();
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| format!("{}: {}", change.is_breaking(), change))
+                .collect::<Vec<_>>(),
+            vec![
+                "true: field `A.c` changed type from `uint32` to `uint64`",
+                "true: field `A.d` was moved from position 2 to 3",
+                "true: field `A.e` was moved from position 3 to 2",
+                "true: field `A.f` was renamed to `ff`",
+                "false: field `A.k` was appended",
+                "true: struct `G` was renamed to `Gg`",
+                "true: struct `I` was removed",
+                "false: struct `L` was added",
+            ]
+        );
+        assert!(diff(&old, &old).is_empty());
+
+        let inserted = parse_schema(
+            "package demo
+type i struct {
+	k text
+	j bool
+}
+",
+        )
+        .unwrap();
+        let changes = diff(
+            &parse_schema("package demo\ntype i struct {\n\tj bool\n}\n").unwrap(),
+            &inserted,
+        );
+        assert_eq!(
+            changes,
+            vec![
+                Change::FieldMoved {
+                    struct_name: "I".to_string(),
+                    field: "j".to_string(),
+                    old_index: 0,
+                    new_index: 1,
+                },
+                Change::FieldInserted {
+                    struct_name: "I".to_string(),
+                    field: "k".to_string(),
+                    index: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_schemas() {
+        let parse = |source| crate::parser::parse(source).unwrap();
+        let old = Schema::new(vec![
+            parse("package shop\ntype order struct {\n\tbuyer people.person\n}\n"),
+            parse("package people\ntype person struct {\n\tname text\n}\n"),
+        ]);
+        let new = Schema::new(vec![
+            parse("package shop\ntype order struct {\n\tbuyer people.person\n\tn uint8\n}\n"),
+            parse("package stock\ntype item struct {\n\tname text\n}\n"),
+        ]);
+        assert_eq!(
+            diff_schemas(&old, &new)
+                .iter()
+                .map(|change| format!("{}: {}", change.is_breaking(), change))
+                .collect::<Vec<_>>(),
+            vec![
+                "false: field `shop.Order.n` was appended",
+                "true: package `people` was removed",
+                "false: package `stock` was added",
+            ]
+        );
+        assert!(diff_schemas(&old, &old).is_empty());
+    }
+}
@@ -1,43 +1,470 @@
 use std::path::{Path, PathBuf};
 
+use anyhow::Context;
 use case::CaseExt;
 
-use crate::generator::generate;
-use crate::parser::parse;
+use crate::ast::{Colfer, Field, FieldType, Schema, Struct};
+use crate::generator::{CodeGenerator, Generator, RustGenerator};
+use crate::parse_schema_files;
+use crate::parser::parse_type;
 
+/// The file generated by [`Config::include_file`], which `colfer::include_schema!` includes.
+const INCLUDE_FILE: &str = "_colfer_includes.rs";
+
+#[derive(Default)]
 pub struct Config {
-    out_dir: PathBuf,
+    out_dir: Option<PathBuf>,
+    pub(crate) views: bool,
+    pub(crate) strict: bool,
+    pub(crate) skip_unknown_fields: bool,
+    pub(crate) retain_unknown_fields: bool,
+    pub(crate) serde: bool,
+    pub(crate) derive_eq: bool,
+    pub(crate) derive_hash: bool,
+    pub(crate) derive_partial_ord: bool,
+    field_visibility: Option<String>,
+    include_file: bool,
+    type_mappings: Vec<(String, String)>,
+    field_mappings: Vec<(String, String)>,
+    type_attributes: Vec<(String, String)>,
+    field_attributes: Vec<(String, String)>,
+    generator: Option<Box<dyn Generator>>,
+    pub(crate) code_generators: Vec<Box<dyn CodeGenerator>>,
 }
 
-impl Default for Config {
-    fn default() -> Self {
+impl Config {
+    /// Set the directory of the generated files, which defaults to `OUT_DIR` of build scripts.
+    pub fn out_dir(self, path: impl Into<PathBuf>) -> Self {
         Self {
-            out_dir: std::env::var("OUT_DIR").unwrap().into(),
+            out_dir: Some(path.into()),
+            ..self
         }
     }
-}
 
-impl Config {
-    pub fn out_dir(self, path: impl Into<PathBuf>) -> Self {
+    /// Generate code with `generator`, such as [`TypeScriptGenerator`](crate::TypeScriptGenerator),
+    /// instead of Rust modules.
+    pub fn generator(self, generator: impl Generator + 'static) -> Self {
         Self {
-            out_dir: path.into(),
+            generator: Some(Box::new(generator)),
+            ..self
         }
     }
 
-    pub fn compile<P: AsRef<Path>>(self, files: &[P]) -> anyhow::Result<()> {
-        for file in files {
-            let source = std::fs::read_to_string(file)?;
-            let colfer = parse(&source).map_err(|err| anyhow::anyhow!(err.to_string()))?;
+    /// Also generate `_colfer_includes.rs`, which declares the module of every package and
+    /// includes its file, to be included with [`colfer::include_schema!`]. Only supported by
+    /// the Rust generator.
+    pub fn include_file(self, enabled: bool) -> Self {
+        Self {
+            include_file: enabled,
+            ..self
+        }
+    }
 
-            colfer.validate()?;
-            std::fs::write(
-                self.out_dir
+    /// Add the hooks of `code_generator` to the generated Rust modules, after those added
+    /// before. Hooks are Rust code, so unlike the other options they are only available in
+    /// build scripts and not in `colfer-gen`.
+    pub fn code_generator(mut self, code_generator: impl CodeGenerator + 'static) -> Self {
+        self.code_generators.push(Box::new(code_generator));
+        self
+    }
+
+    /// Also generate a borrowed `<Name>View<'a>` type for every struct, which decodes text
+    /// and binary fields from the input without copying.
+    pub fn views(self, enabled: bool) -> Self {
+        Self {
+            views: enabled,
+            ..self
+        }
+    }
+
+    /// Generate strict decoders, which reject unknown, out-of-order and duplicate field ids,
+    /// a missing end marker, and trailing bytes after a message decoded with `from_bytes`.
+    pub fn strict(self, enabled: bool) -> Self {
+        Self {
+            strict: enabled,
+            ..self
+        }
+    }
+
+    /// Generate lenient decoders, which accept unknown fields from a newer schema at the end
+    /// of a message decoded with `from_bytes`.
+    ///
+    /// Colfer fields do not carry their length, so unknown fields can only be skipped at the
+    /// end of the outermost message. Unknown fields in nested messages, or in messages decoded
+    /// from a stream, fail with `ErrorKind::UnknownField`.
+    pub fn skip_unknown_fields(self, enabled: bool) -> Self {
+        Self {
+            skip_unknown_fields: enabled,
+            ..self
+        }
+    }
+
+    /// Like [`Config::skip_unknown_fields`], and also retain the raw unknown fields in an
+    /// `unknown_fields` field of every generated struct, which is written again by `encode`.
+    pub fn retain_unknown_fields(self, enabled: bool) -> Self {
+        Self {
+            retain_unknown_fields: enabled,
+            ..self
+        }
+    }
+
+    /// Derive `Serialize` and `Deserialize` for the generated structs, with fields named as in
+    /// the schema. Requires the `serde` feature of the `colfer` crate.
+    pub fn serde(self, enabled: bool) -> Self {
+        Self {
+            serde: enabled,
+            ..self
+        }
+    }
+
+    /// Also derive `Eq` for the generated structs, except for those with floating point
+    /// fields, directly or through nested structs.
+    pub fn derive_eq(self, enabled: bool) -> Self {
+        Self {
+            derive_eq: enabled,
+            ..self
+        }
+    }
+
+    /// Also derive `Hash` for the generated structs, except for those with floating point
+    /// fields, directly or through nested structs.
+    pub fn derive_hash(self, enabled: bool) -> Self {
+        Self {
+            derive_hash: enabled,
+            ..self
+        }
+    }
+
+    /// Also derive `PartialOrd` for the generated structs, which compares fields in schema
+    /// order.
+    pub fn derive_partial_ord(self, enabled: bool) -> Self {
+        Self {
+            derive_partial_ord: enabled,
+            ..self
+        }
+    }
+
+    /// Set the visibility of the fields of generated structs and views, such as `pub(crate)`,
+    /// or `""` for private fields. Defaults to `pub`.
+    pub fn field_visibility(self, visibility: impl Into<String>) -> Self {
+        Self {
+            field_visibility: Some(visibility.into()),
+            ..self
+        }
+    }
+
+    /// Add the attribute `attribute`, such as `#[non_exhaustive]`, to the generated structs
+    /// matching `path`, which is either `.` for all structs, a `package` or a
+    /// `package.struct`. Views are not affected.
+    pub fn type_attribute(mut self, path: impl Into<String>, attribute: impl Into<String>) -> Self {
+        self.type_attributes.push((path.into(), attribute.into()));
+        self
+    }
+
+    /// Add the attribute `attribute` to the fields of generated structs matching `path`, which
+    /// is either `.` for all fields, a `package`, a `package.struct` or a
+    /// `package.struct.field`. Views are not affected.
+    pub fn field_attribute(
+        mut self,
+        path: impl Into<String>,
+        attribute: impl Into<String>,
+    ) -> Self {
+        self.field_attributes.push((path.into(), attribute.into()));
+        self
+    }
+
+    /// Returns the attributes to add to struct `s`.
+    pub(crate) fn type_attributes<'a>(
+        &'a self,
+        colfer: &'a Colfer,
+        s: &'a Struct,
+    ) -> impl Iterator<Item = &'a str> {
+        self.type_attributes
+            .iter()
+            .filter(move |(path, _)| path_matches(path, colfer, s, None))
+            .map(|(_, attribute)| attribute.as_str())
+    }
+
+    /// Returns the attributes to add to field `f` of struct `s`.
+    pub(crate) fn field_attributes<'a>(
+        &'a self,
+        colfer: &'a Colfer,
+        s: &'a Struct,
+        f: &'a Field,
+    ) -> impl Iterator<Item = &'a str> {
+        self.field_attributes
+            .iter()
+            .filter(move |(path, _)| path_matches(path, colfer, s, Some(f)))
+            .map(|(_, attribute)| attribute.as_str())
+    }
+
+    /// Returns the visibility of generated fields, which is empty if private.
+    pub(crate) fn generated_field_visibility(&self) -> &str {
+        self.field_visibility.as_deref().map_or("pub", str::trim)
+    }
+
+    /// Map the fields of schema type `schema_type`, such as `binary` or `[]float64`, to the
+    /// Rust type `rust_type`, which must implement `colfer::Type`. The schema type `[]` maps
+    /// all lists. In the type of a list, `{}` is replaced by the Rust type of its elements,
+    /// e.g. `smallvec::SmallVec<[{}; 4]>`.
+    ///
+    /// The `colfer` crate implements `Type` for `std::sync::Arc<str>`, and behind features of
+    /// the same name for `bytes::Bytes`, `smol_str::SmolStr`, `smallvec::SmallVec` and
+    /// `chrono::DateTime<Utc>`. Struct fields map to an `Option` of a pointer to the struct,
+    /// such as `Option<std::sync::Arc<T>>`. Borrowed view types are not affected.
+    ///
+    /// If a schema type is mapped more than once, the last mapping wins.
+    pub fn map_type(
+        mut self,
+        schema_type: impl Into<String>,
+        rust_type: impl Into<String>,
+    ) -> Self {
+        self.type_mappings
+            .push((schema_type.into(), rust_type.into()));
+        self
+    }
+
+    /// Map the field `path`, written as `package.struct.field`, to the Rust type `rust_type`
+    /// like [`Config::map_type`] does, which it takes precedence over. If a field is mapped
+    /// more than once, the last mapping wins.
+    pub fn map_field(mut self, path: impl Into<String>, rust_type: impl Into<String>) -> Self {
+        self.field_mappings.push((path.into(), rust_type.into()));
+        self
+    }
+
+    /// Returns the Rust type that field `f` of struct `s` is mapped to, if any, with `{}` still
+    /// to be replaced by the element type of lists.
+    pub(crate) fn mapped_type(&self, colfer: &Colfer, s: &Struct, f: &Field) -> Option<&str> {
+        let field = self
+            .field_mappings
+            .iter()
+            .rev()
+            .find(|(path, _)| field_path_matches(path, colfer, s, f));
+        let ty = || {
+            self.type_mappings
+                .iter()
+                .rev()
+                .find(|(schema_type, _)| parse_type(schema_type).as_ref() == Some(&f.ty))
+        };
+        let list = || {
+            self.type_mappings
+                .iter()
+                .rev()
+                .find(|(schema_type, _)| schema_type.trim() == "[]" && is_list(&f.ty))
+        };
+        field
+            .or_else(ty)
+            .or_else(list)
+            .map(|(_, rust_type)| rust_type.as_str())
+    }
+
+    fn check_mappings(&self, schema: &Schema) -> anyhow::Result<()> {
+        for (schema_type, _) in &self.type_mappings {
+            let known = match parse_type(schema_type) {
+                Some(FieldType::Struct(name)) | Some(FieldType::ArrayStruct(name)) => schema
+                    .packages
+                    .iter()
+                    .any(|colfer| schema.resolve(&colfer.package, &name).is_some()),
+                Some(_) => true,
+                None => schema_type.trim() == "[]",
+            };
+            if !known {
+                anyhow::bail!("Unknown schema type `{}` in a type mapping.", schema_type);
+            }
+        }
+        for (path, _) in &self.field_mappings {
+            let found = schema.packages.iter().any(|colfer| {
+                colfer.structs.iter().any(|s| {
+                    s.fields
+                        .iter()
+                        .any(|f| field_path_matches(path, colfer, s, f))
+                })
+            });
+            if !found {
+                anyhow::bail!(
+                    "No field `{}` to map, expected `package.struct.field`.",
+                    path
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn lenient(&self) -> bool {
+        self.skip_unknown_fields || self.retain_unknown_fields
+    }
+
+    /// Generate the code for the schema `files`, returning the path of every generated file
+    /// along with its contents, without writing anything. One file is generated per package,
+    /// which must be a module next to the modules of the packages it references.
+    pub fn generate<P: AsRef<Path>>(&self, files: &[P]) -> anyhow::Result<Vec<(PathBuf, String)>> {
+        if self.strict && self.lenient() {
+            anyhow::bail!("Strict decoding cannot be combined with skipping unknown fields.");
+        }
+
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .context("No output directory, set `out_dir` or `OUT_DIR`.")?,
+        };
+
+        let schema = parse_schema_files(files)?;
+        self.check_mappings(&schema)?;
+        let generator = self.generator.as_deref().unwrap_or(&RustGenerator);
+        let mut files = schema
+            .packages
+            .iter()
+            .map(|colfer| {
+                let path = out_dir
                     .join(colfer.package.to_snake())
-                    .with_extension("rs"),
-                generate(&colfer)?,
-            )?;
+                    .with_extension(generator.file_extension());
+                Ok((path, generator.generate(&schema, colfer, self)?))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        if self.include_file {
+            let code = generator
+                .generate_include_file(&schema)?
+                .context("The generator does not support an include file.")?;
+            files.push((out_dir.join(INCLUDE_FILE), code));
         }
+        Ok(files)
+    }
 
+    /// Generate the code for the schema `files` and write it to the output directory.
+    pub fn compile<P: AsRef<Path>>(self, files: &[P]) -> anyhow::Result<()> {
+        for (path, code) in self.generate(files)? {
+            std::fs::write(&path, code)
+                .with_context(|| format!("Failed to write `{}`.", path.display()))?;
+        }
         Ok(())
     }
 }
+
+/// Returns `true` if `path` is `.`, or names the package of struct `s`, struct `s`, or field
+/// `f` of struct `s`.
+fn path_matches(path: &str, colfer: &Colfer, s: &Struct, f: Option<&Field>) -> bool {
+    if path == "." {
+        return true;
+    }
+    match (path.split('.').collect::<Vec<_>>().as_slice(), f) {
+        ([package], _) => package.to_snake() == colfer.package,
+        ([package, struct_name], _) => {
+            package.to_snake() == colfer.package && struct_name.to_camel() == s.name
+        }
+        ([package, struct_name, field], Some(f)) => {
+            package.to_snake() == colfer.package
+                && struct_name.to_camel() == s.name
+                && (*field == f.schema_name || *field == f.name)
+        }
+        _ => false,
+    }
+}
+
+fn field_path_matches(path: &str, colfer: &Colfer, s: &Struct, f: &Field) -> bool {
+    path.split('.').count() == 3 && path_matches(path, colfer, s, Some(f))
+}
+
+fn is_list(ty: &FieldType) -> bool {
+    matches!(
+        ty,
+        FieldType::ArrayU16
+            | FieldType::ArrayU32
+            | FieldType::ArrayU64
+            | FieldType::ArrayI8
+            | FieldType::ArrayI16
+            | FieldType::ArrayI32
+            | FieldType::ArrayI64
+            | FieldType::ArrayF32
+            | FieldType::ArrayF64
+            | FieldType::ArrayText
+            | FieldType::ArrayBinary
+            | FieldType::ArrayStruct(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_type() {
+        let schema = Schema::new(vec![crate::parse_schema(
+            "package demo
+type a struct {
+	s text
+	ss []text
+	fs []float64
+	b a
+}
+",
+        )
+        .unwrap()]);
+        // The last mapping wins.
+        let config = Config::default()
+            .map_type("[]", "smallvec::SmallVec<[{}; 4]>")
+            .map_type("text", "Box<str>")
+            .map_type("text", "std::sync::Arc<str>")
+            .map_field("demo.a.b", "Option<std::rc::Rc<A>>")
+            .map_type("[]text", "Vec<std::sync::Arc<str>>")
+            .map_field("demo.a.b", "Option<std::sync::Arc<A>>");
+        config.check_mappings(&schema).unwrap();
+
+        let colfer = &schema.packages[0];
+        let s = &colfer.structs[0];
+        let mapped: Vec<_> = s
+            .fields
+            .iter()
+            .map(|f| config.mapped_type(colfer, s, f))
+            .collect();
+        assert_eq!(
+            mapped,
+            vec![
+                Some("std::sync::Arc<str>"),
+                Some("Vec<std::sync::Arc<str>>"),
+                Some("smallvec::SmallVec<[{}; 4]>"),
+                Some("Option<std::sync::Arc<A>>"),
+            ]
+        );
+
+        assert!(Config::default()
+            .map_type("string", "String")
+            .check_mappings(&schema)
+            .is_err());
+        assert!(Config::default()
+            .map_field("demo.a.c", "String")
+            .check_mappings(&schema)
+            .is_err());
+    }
+
+    #[test]
+    fn test_attributes() {
+        let schema = Schema::new(vec![crate::parse_schema(
+            "package demo
+type a struct {
+	b bool
+}
+",
+        )
+        .unwrap()]);
+        let config = Config::default()
+            .type_attribute(".", "#[all]")
+            .type_attribute("demo", "#[package]")
+            .type_attribute("demo.b", "#[other]")
+            .field_attribute("demo.a", "#[fields]")
+            .field_attribute("demo.a.b", "#[field]")
+            .field_attribute("demo.a.c", "#[other]");
+        let colfer = &schema.packages[0];
+        let s = &colfer.structs[0];
+        assert_eq!(
+            config.type_attributes(colfer, s).collect::<Vec<_>>(),
+            vec!["#[all]", "#[package]"]
+        );
+        assert_eq!(
+            config
+                .field_attributes(colfer, s, &s.fields[0])
+                .collect::<Vec<_>>(),
+            vec!["#[fields]", "#[field]"]
+        );
+    }
+}
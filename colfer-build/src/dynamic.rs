@@ -0,0 +1,449 @@
+//! Encoding and decoding of messages whose schema is only known at runtime.
+
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+
+use colfer::{DateTime, DecodeLimits, Error, ErrorKind, LimitError, Result, Type};
+
+use crate::ast::{Colfer, FieldType, Schema, Struct};
+
+/// The value of a field, with a variant for every [`FieldType`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    Timestamp(DateTime),
+    Text(String),
+    Binary(Vec<u8>),
+    Opaque8([u8; 1]),
+    Opaque16([u8; 2]),
+    Opaque32([u8; 4]),
+    Opaque64([u8; 8]),
+    Struct(DynamicMessage),
+    ArrayU16(Vec<u16>),
+    ArrayU32(Vec<u32>),
+    ArrayU64(Vec<u64>),
+    ArrayI8(Vec<i8>),
+    ArrayI16(Vec<i16>),
+    ArrayI32(Vec<i32>),
+    ArrayI64(Vec<i64>),
+    ArrayF32(Vec<f32>),
+    ArrayF64(Vec<f64>),
+    ArrayText(Vec<String>),
+    ArrayBinary(Vec<Vec<u8>>),
+    ArrayStruct(Vec<DynamicMessage>),
+}
+
+/// A message of a struct from a schema, holding its fields by their name in the schema.
+///
+/// The struct is named as `package.Name`, or as `Name` if only one package of the schema
+/// defines it. Decoded messages are named with their package. Fields that are absent have
+/// their zero value, as colfer does not encode zero values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicMessage {
+    name: String,
+    fields: BTreeMap<String, Value>,
+}
+
+fn error(msg: String) -> Error {
+    Error::new(ErrorKind::SchemaMismatch(msg))
+}
+
+/// Returns the struct `name` of `schema` along with its package, see [`Schema::find_struct`].
+pub fn find_struct<'a>(schema: &'a Schema, name: &str) -> Result<(&'a Colfer, &'a Struct)> {
+    schema.find_struct(name).ok_or_else(|| {
+        error(format!(
+            "struct `{}` is not defined, or defined by several packages",
+            name
+        ))
+    })
+}
+
+/// Returns the struct referenced as `name` by a field of a struct of package `colfer`.
+fn resolve<'a>(
+    schema: &'a Schema,
+    colfer: &Colfer,
+    name: &str,
+) -> Result<(&'a Colfer, &'a Struct)> {
+    schema
+        .resolve(&colfer.package, name)
+        .ok_or_else(|| error(format!("struct `{}` is not defined", name)))
+}
+
+impl DynamicMessage {
+    /// Creates an empty message of struct `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the name of the struct.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of field `name`, or `None` if it is absent.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields.get(name)
+    }
+
+    /// Sets the value of field `name`, returning the previous value.
+    pub fn set(&mut self, name: impl Into<String>, value: Value) -> Option<Value> {
+        self.fields.insert(name.into(), value)
+    }
+
+    /// Removes field `name`, returning its value.
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.fields.remove(name)
+    }
+
+    /// Returns an iterator over the fields that are present, ordered by name.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Decodes a message of struct `name` of `schema` from `data`.
+    pub fn from_bytes(schema: &Schema, name: &str, data: &[u8]) -> Result<Self> {
+        Self::from_bytes_with_limits(schema, name, data, &DecodeLimits::default())
+    }
+
+    /// Decodes a message of struct `name` of `schema` from `data`, failing with
+    /// `ErrorKind::LimitExceeded` if the input exceeds `limits`.
+    ///
+    /// Decoding is strict, unknown fields and trailing bytes are rejected.
+    pub fn from_bytes_with_limits(
+        schema: &Schema,
+        name: &str,
+        data: &[u8],
+        limits: &DecodeLimits,
+    ) -> Result<Self> {
+        let (colfer, s) = find_struct(schema, name)?;
+        if data.len() > limits.max_size {
+            return Err(LimitError::Size.into());
+        }
+        let mut r = Cursor::new(data);
+        let message = decode_fields(schema, colfer, s, &mut r, limits)
+            .map_err(|err| err.with_offset(r.position()))?;
+        if r.position() != data.len() as u64 {
+            return Err(Error::new(ErrorKind::TrailingData).with_offset(r.position()));
+        }
+        Ok(message)
+    }
+
+    /// Encodes the message with `schema` to writer `W`.
+    pub fn encode<W: Write>(&self, schema: &Schema, w: &mut W) -> Result<()> {
+        let (colfer, s) = find_struct(schema, &self.name)?;
+        encode_fields(schema, colfer, s, self, w)
+    }
+
+    /// Encodes the message with `schema` to `Vec<u8>`.
+    pub fn to_vec(&self, schema: &Schema) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.encode(schema, &mut data)?;
+        Ok(data)
+    }
+}
+
+fn decode_fields<R: Read>(
+    schema: &Schema,
+    colfer: &Colfer,
+    s: &Struct,
+    r: &mut R,
+    limits: &DecodeLimits,
+) -> Result<DynamicMessage> {
+    let mut message = DynamicMessage::new(format!("{}.{}", colfer.package, s.name));
+    let (mut id, mut flag) = colfer::read_header(r)?;
+    for (idx, f) in s.fields.iter().enumerate() {
+        if id as usize == idx {
+            let value = decode_value(schema, colfer, &f.ty, r, flag, limits)?;
+            message.fields.insert(f.schema_name.clone(), value);
+            let next = colfer::read_header(r)?;
+            id = next.0;
+            flag = next.1;
+        }
+    }
+    colfer::check_end(id, s.fields.len() as u8)?;
+    Ok(message)
+}
+
+fn decode_value<R: Read>(
+    schema: &Schema,
+    colfer: &Colfer,
+    ty: &FieldType,
+    r: &mut R,
+    flag: bool,
+    limits: &DecodeLimits,
+) -> Result<Value> {
+    Ok(match ty {
+        FieldType::Bool => Value::Bool(Type::decode(r, flag, limits)?),
+        FieldType::U8 => Value::U8(Type::decode(r, flag, limits)?),
+        FieldType::U16 => Value::U16(Type::decode(r, flag, limits)?),
+        FieldType::U32 => Value::U32(Type::decode(r, flag, limits)?),
+        FieldType::U64 => Value::U64(Type::decode(r, flag, limits)?),
+        FieldType::I8 => Value::I8(Type::decode(r, flag, limits)?),
+        FieldType::I16 => Value::I16(Type::decode(r, flag, limits)?),
+        FieldType::I32 => Value::I32(Type::decode(r, flag, limits)?),
+        FieldType::I64 => Value::I64(Type::decode(r, flag, limits)?),
+        FieldType::F32 => Value::F32(Type::decode(r, flag, limits)?),
+        FieldType::F64 => Value::F64(Type::decode(r, flag, limits)?),
+        FieldType::Timestamp => Value::Timestamp(Type::decode(r, flag, limits)?),
+        FieldType::Text => Value::Text(Type::decode(r, flag, limits)?),
+        FieldType::Binary => Value::Binary(Type::decode(r, flag, limits)?),
+        FieldType::Opaque8 => Value::Opaque8(Type::decode(r, flag, limits)?),
+        FieldType::Opaque16 => Value::Opaque16(Type::decode(r, flag, limits)?),
+        FieldType::Opaque32 => Value::Opaque32(Type::decode(r, flag, limits)?),
+        FieldType::Opaque64 => Value::Opaque64(Type::decode(r, flag, limits)?),
+        FieldType::Struct(name) => {
+            let (colfer, s) = resolve(schema, colfer, name)?;
+            Value::Struct(decode_fields(schema, colfer, s, r, &limits.nested()?)?)
+        }
+        FieldType::ArrayU16 => Value::ArrayU16(Type::decode(r, flag, limits)?),
+        FieldType::ArrayU32 => Value::ArrayU32(Type::decode(r, flag, limits)?),
+        FieldType::ArrayU64 => Value::ArrayU64(Type::decode(r, flag, limits)?),
+        FieldType::ArrayI8 => Value::ArrayI8(Type::decode(r, flag, limits)?),
+        FieldType::ArrayI16 => Value::ArrayI16(Type::decode(r, flag, limits)?),
+        FieldType::ArrayI32 => Value::ArrayI32(Type::decode(r, flag, limits)?),
+        FieldType::ArrayI64 => Value::ArrayI64(Type::decode(r, flag, limits)?),
+        FieldType::ArrayF32 => Value::ArrayF32(Type::decode(r, flag, limits)?),
+        FieldType::ArrayF64 => Value::ArrayF64(Type::decode(r, flag, limits)?),
+        FieldType::ArrayText => Value::ArrayText(Type::decode(r, flag, limits)?),
+        FieldType::ArrayBinary => Value::ArrayBinary(Type::decode(r, flag, limits)?),
+        FieldType::ArrayStruct(name) => {
+            let (colfer, s) = resolve(schema, colfer, name)?;
+            let len = colfer::decode_list_len(r, limits)?;
+            let nested = limits.nested()?;
+            let mut messages = Vec::new();
+            for _ in 0..len {
+                messages.push(decode_fields(schema, colfer, s, r, &nested)?);
+            }
+            Value::ArrayStruct(messages)
+        }
+    })
+}
+
+fn encode_fields<W: Write>(
+    schema: &Schema,
+    colfer: &Colfer,
+    s: &Struct,
+    message: &DynamicMessage,
+    w: &mut W,
+) -> Result<()> {
+    if let Some(name) = message
+        .fields
+        .keys()
+        .find(|name| !s.fields.iter().any(|f| &f.schema_name == *name))
+    {
+        return Err(error(format!(
+            "struct `{}` has no field `{}`",
+            s.name, name
+        )));
+    }
+
+    for (idx, f) in s.fields.iter().enumerate() {
+        if let Some(value) = message.fields.get(&f.schema_name) {
+            if !matches_type(&f.ty, value) {
+                return Err(error(format!(
+                    "field `{}.{}` does not hold a value of type `{}`",
+                    s.name, f.schema_name, f.ty
+                )));
+            }
+            encode_value(schema, colfer, &f.ty, value, w, idx as u8)?;
+        }
+    }
+    colfer::write_end(w)
+}
+
+fn matches_type(ty: &FieldType, value: &Value) -> bool {
+    matches!(
+        (ty, value),
+        (FieldType::Bool, Value::Bool(_))
+            | (FieldType::U8, Value::U8(_))
+            | (FieldType::U16, Value::U16(_))
+            | (FieldType::U32, Value::U32(_))
+            | (FieldType::U64, Value::U64(_))
+            | (FieldType::I8, Value::I8(_))
+            | (FieldType::I16, Value::I16(_))
+            | (FieldType::I32, Value::I32(_))
+            | (FieldType::I64, Value::I64(_))
+            | (FieldType::F32, Value::F32(_))
+            | (FieldType::F64, Value::F64(_))
+            | (FieldType::Timestamp, Value::Timestamp(_))
+            | (FieldType::Text, Value::Text(_))
+            | (FieldType::Binary, Value::Binary(_))
+            | (FieldType::Opaque8, Value::Opaque8(_))
+            | (FieldType::Opaque16, Value::Opaque16(_))
+            | (FieldType::Opaque32, Value::Opaque32(_))
+            | (FieldType::Opaque64, Value::Opaque64(_))
+            | (FieldType::Struct(_), Value::Struct(_))
+            | (FieldType::ArrayU16, Value::ArrayU16(_))
+            | (FieldType::ArrayU32, Value::ArrayU32(_))
+            | (FieldType::ArrayU64, Value::ArrayU64(_))
+            | (FieldType::ArrayI8, Value::ArrayI8(_))
+            | (FieldType::ArrayI16, Value::ArrayI16(_))
+            | (FieldType::ArrayI32, Value::ArrayI32(_))
+            | (FieldType::ArrayI64, Value::ArrayI64(_))
+            | (FieldType::ArrayF32, Value::ArrayF32(_))
+            | (FieldType::ArrayF64, Value::ArrayF64(_))
+            | (FieldType::ArrayText, Value::ArrayText(_))
+            | (FieldType::ArrayBinary, Value::ArrayBinary(_))
+            | (FieldType::ArrayStruct(_), Value::ArrayStruct(_))
+    )
+}
+
+fn encode_value<W: Write>(
+    schema: &Schema,
+    colfer: &Colfer,
+    ty: &FieldType,
+    value: &Value,
+    w: &mut W,
+    id: u8,
+) -> Result<()> {
+    match (ty, value) {
+        (_, Value::Bool(v)) => v.encode(w, id),
+        (_, Value::U8(v)) => v.encode(w, id),
+        (_, Value::U16(v)) => v.encode(w, id),
+        (_, Value::U32(v)) => v.encode(w, id),
+        (_, Value::U64(v)) => v.encode(w, id),
+        (_, Value::I8(v)) => v.encode(w, id),
+        (_, Value::I16(v)) => v.encode(w, id),
+        (_, Value::I32(v)) => v.encode(w, id),
+        (_, Value::I64(v)) => v.encode(w, id),
+        (_, Value::F32(v)) => v.encode(w, id),
+        (_, Value::F64(v)) => v.encode(w, id),
+        (_, Value::Timestamp(v)) => v.encode(w, id),
+        (_, Value::Text(v)) => v.encode(w, id),
+        (_, Value::Binary(v)) => v.encode(w, id),
+        (_, Value::Opaque8(v)) => v.encode(w, id),
+        (_, Value::Opaque16(v)) => v.encode(w, id),
+        (_, Value::Opaque32(v)) => v.encode(w, id),
+        (_, Value::Opaque64(v)) => v.encode(w, id),
+        (FieldType::Struct(name), Value::Struct(message)) => {
+            w.write_all(&[id])?;
+            let (colfer, s) = resolve(schema, colfer, name)?;
+            encode_fields(schema, colfer, s, message, w)
+        }
+        (_, Value::ArrayU16(v)) => v.encode(w, id),
+        (_, Value::ArrayU32(v)) => v.encode(w, id),
+        (_, Value::ArrayU64(v)) => v.encode(w, id),
+        (_, Value::ArrayI8(v)) => v.encode(w, id),
+        (_, Value::ArrayI16(v)) => v.encode(w, id),
+        (_, Value::ArrayI32(v)) => v.encode(w, id),
+        (_, Value::ArrayI64(v)) => v.encode(w, id),
+        (_, Value::ArrayF32(v)) => v.encode(w, id),
+        (_, Value::ArrayF64(v)) => v.encode(w, id),
+        (_, Value::ArrayText(v)) => v.encode(w, id),
+        (_, Value::ArrayBinary(v)) => v.encode(w, id),
+        (FieldType::ArrayStruct(name), Value::ArrayStruct(messages)) => {
+            let (colfer, s) = resolve(schema, colfer, name)?;
+            if !messages.is_empty() {
+                colfer::encode_list_header(w, id, messages.len())?;
+                for message in messages {
+                    encode_fields(schema, colfer, s, message, w)?;
+                }
+            }
+            Ok(())
+        }
+        _ => unreachable!("checked by `matches_type`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_schema;
+
+    const SCHEMA: &str = r#"
+package demo
+
+type node struct {
+	name text
+	weight float64
+	children []node
+	parent node
+	tags []text
+	ids []int64
+	hash opaque32
+}
+"#;
+
+    #[test]
+    fn test_roundtrip() {
+        let schema = Schema::new(vec![parse_schema(SCHEMA).unwrap()]);
+
+        let mut child = DynamicMessage::new("demo.Node");
+        child.set("name", Value::Text("child".to_string()));
+        let mut parent = DynamicMessage::new("demo.Node");
+        parent.set("weight", Value::F64(-1.5));
+        let mut root = DynamicMessage::new("demo.Node");
+        root.set("name", Value::Text("root".to_string()));
+        root.set("children", Value::ArrayStruct(vec![child.clone(), child]));
+        root.set("parent", Value::Struct(parent));
+        root.set("tags", Value::ArrayText(vec!["a".to_string()]));
+        root.set("ids", Value::ArrayI64(vec![-1, i64::MAX]));
+        root.set("hash", Value::Opaque32([0, 1, 2, 3]));
+
+        let data = root.to_vec(&schema).unwrap();
+        let decoded = DynamicMessage::from_bytes(&schema, "Node", &data).unwrap();
+        assert_eq!(decoded, root);
+        assert_eq!(decoded.get("weight"), None);
+        assert_eq!(
+            decoded.fields().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["children", "hash", "ids", "name", "parent", "tags"]
+        );
+    }
+
+    #[test]
+    fn test_cross_package() {
+        let parse = |source| crate::parser::parse(source).unwrap();
+        let shop = parse("package shop\ntype order struct {\n\tbuyer people.person\n}\n");
+        let people = parse("package people\ntype person struct {\n\tname text\n}\n");
+        let schema = Schema::new(vec![shop, people]);
+
+        let mut buyer = DynamicMessage::new("people.Person");
+        buyer.set("name", Value::Text("ann".to_string()));
+        let mut order = DynamicMessage::new("shop.Order");
+        order.set("buyer", Value::Struct(buyer));
+
+        let data = order.to_vec(&schema).unwrap();
+        let decoded = DynamicMessage::from_bytes(&schema, "Order", &data).unwrap();
+        assert_eq!(decoded, order);
+    }
+
+    #[test]
+    fn test_errors() {
+        let schema = Schema::new(vec![parse_schema(SCHEMA).unwrap()]);
+
+        let mut message = DynamicMessage::new("demo.Node");
+        message.set("name", Value::U32(1));
+        let err = message.to_vec(&schema).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::SchemaMismatch(_)));
+        assert_eq!(
+            err.kind().to_string(),
+            "field `Node.name` does not hold a value of type `text`"
+        );
+
+        let mut message = DynamicMessage::new("demo.Node");
+        message.set("size", Value::U32(1));
+        assert!(message.to_vec(&schema).is_err());
+
+        assert!(DynamicMessage::new("Missing").to_vec(&schema).is_err());
+
+        let err = DynamicMessage::from_bytes(&schema, "Node", &[9, 0x7f]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnknownField(9)));
+        let err = DynamicMessage::from_bytes(&schema, "Node", &[0x7f, 0]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TrailingData));
+    }
+}
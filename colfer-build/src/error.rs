@@ -0,0 +1,163 @@
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use crate::ast::Span;
+
+/// A problem in a schema, located by a span of its source.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub message: String,
+    /// A hint on how to fix the problem.
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            span,
+            message: message.into(),
+            help: None,
+        }
+    }
+
+    pub fn with_help(self, help: impl Into<String>) -> Self {
+        Self {
+            help: Some(help.into()),
+            ..self
+        }
+    }
+}
+
+/// The problems found in a schema, displayed like compiler errors with the file, line and
+/// column of every problem and a snippet of the offending line.
+#[derive(Debug)]
+pub struct SchemaError {
+    files: Vec<FileDiagnostics>,
+}
+
+#[derive(Debug)]
+struct FileDiagnostics {
+    file: Option<PathBuf>,
+    source: String,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl SchemaError {
+    pub(crate) fn new(file: Option<&Path>, source: &str, diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            files: vec![FileDiagnostics {
+                file: file.map(Path::to_path_buf),
+                source: source.to_string(),
+                diagnostics,
+            }],
+        }
+    }
+
+    /// Combines the errors of several schema files.
+    pub(crate) fn merge(errors: Vec<SchemaError>) -> Self {
+        Self {
+            files: errors.into_iter().flat_map(|err| err.files).collect(),
+        }
+    }
+
+    /// Returns the problems that were found, along with their schema file if the schema was
+    /// read from a file.
+    pub fn diagnostics(&self) -> impl Iterator<Item = (Option<&Path>, &Diagnostic)> {
+        self.files.iter().flat_map(|file| {
+            file.diagnostics
+                .iter()
+                .map(move |diagnostic| (file.file.as_deref(), diagnostic))
+        })
+    }
+}
+
+impl FileDiagnostics {
+    fn render(&self, f: &mut Formatter<'_>, file: &str, diagnostic: &Diagnostic) -> fmt::Result {
+        let (line, column) = diagnostic.span.line_column(&self.source);
+        let text = self.source.lines().nth(line - 1).unwrap_or("");
+        // Keep tabs, so that the carets line up with the snippet.
+        let indent: String = text
+            .chars()
+            .take(column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = self
+            .source
+            .get(diagnostic.span.start..diagnostic.span.end)
+            .and_then(|spanned| spanned.lines().next())
+            .map_or(0, |spanned| spanned.chars().count())
+            .max(1);
+        let gutter = " ".repeat(line.to_string().len());
+
+        writeln!(f, "error: {}", diagnostic.message)?;
+        writeln!(f, "{}--> {}:{}:{}", gutter, file, line, column)?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line, text)?;
+        write!(f, "{} | {}{}", gutter, indent, "^".repeat(width))?;
+        if let Some(help) = &diagnostic.help {
+            write!(f, "\n{} = help: {}", gutter, help)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for file in &self.files {
+            let name = match &file.file {
+                Some(path) => path.display().to_string(),
+                None => "<schema>".to_string(),
+            };
+            for diagnostic in &file.diagnostics {
+                if !first {
+                    writeln!(f)?;
+                    writeln!(f)?;
+                }
+                first = false;
+                file.render(f, &name, diagnostic)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let source = "package demo\n\ntype a struct {\n\tb point\n\tc bool\n\td bool\n\te bool\n\tf bool\n\tg x y\n}\n";
+        let err = SchemaError::new(
+            Some(Path::new("demo.colf")),
+            source,
+            vec![
+                Diagnostic::new(Span { start: 33, end: 38 }, "struct `Point` is not defined")
+                    .with_help("declare it with `type point struct { .. }`"),
+                Diagnostic::new(
+                    Span { start: 76, end: 77 },
+                    "expected a field or `}`, found `y`",
+                ),
+            ],
+        );
+        assert_eq!(
+            err.to_string(),
+            "error: struct `Point` is not defined
+ --> demo.colf:4:4
+  |
+4 | \tb point
+  | \t  ^^^^^
+  = help: declare it with `type point struct { .. }`
+
+error: expected a field or `}`, found `y`
+ --> demo.colf:9:6
+  |
+9 | \tg x y
+  | \t    ^"
+        );
+    }
+}
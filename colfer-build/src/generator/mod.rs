@@ -0,0 +1,67 @@
+mod rust;
+mod typescript;
+
+pub use rust::RustGenerator;
+pub use typescript::TypeScriptGenerator;
+
+use crate::ast::{Colfer, Field, Schema, Struct};
+use crate::Config;
+
+/// Generates the code of a target language from parsed schemas, one file per package.
+pub trait Generator {
+    /// Returns the extension of the generated files, such as `rs`.
+    fn file_extension(&self) -> &str;
+
+    /// Generates the code of the package `colfer` of `schema`. Generators of other languages
+    /// than Rust may ignore the options of `config`.
+    fn generate(&self, schema: &Schema, colfer: &Colfer, config: &Config)
+        -> anyhow::Result<String>;
+
+    /// Generates a file that includes the files of all packages of `schema`, for
+    /// [`Config::include_file`]. Returns `None` by default, if the language has no use for one.
+    fn generate_include_file(&self, schema: &Schema) -> anyhow::Result<Option<String>> {
+        let _ = schema;
+        Ok(None)
+    }
+}
+
+/// Hooks of [`RustGenerator`] that add code to the generated modules, such as validation or
+/// builder methods. Every hook appends Rust items to `code`, or attributes for fields, which are
+/// parsed and formatted along with the generated code. Hooks do nothing by default.
+pub trait CodeGenerator {
+    /// Adds code at the start of the module of package `colfer`. The generated code refers to
+    /// the `colfer` crate and `std` by full paths, without imports.
+    fn prelude(&self, colfer: &Colfer, code: &mut String) -> anyhow::Result<()> {
+        let _ = (colfer, code);
+        Ok(())
+    }
+
+    /// Adds code after the generated items of struct `s`, such as an `impl` block.
+    fn generate_struct(
+        &self,
+        colfer: &Colfer,
+        s: &Struct,
+        code: &mut String,
+    ) -> anyhow::Result<()> {
+        let _ = (colfer, s, code);
+        Ok(())
+    }
+
+    /// Adds code before the declaration of field `f` of struct `s`, such as attributes.
+    fn generate_field(
+        &self,
+        colfer: &Colfer,
+        s: &Struct,
+        f: &Field,
+        code: &mut String,
+    ) -> anyhow::Result<()> {
+        let _ = (colfer, s, f, code);
+        Ok(())
+    }
+
+    /// Adds code at the end of the module of package `colfer`.
+    fn epilogue(&self, colfer: &Colfer, code: &mut String) -> anyhow::Result<()> {
+        let _ = (colfer, code);
+        Ok(())
+    }
+}
@@ -0,0 +1,734 @@
+use anyhow::Context;
+use case::CaseExt;
+use proc_macro2::{Ident, Literal, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{ParseStream, Parser};
+
+use super::Generator;
+use crate::ast::{Colfer, Field, FieldType, Schema, Struct};
+use crate::Config;
+
+/// Generates Rust modules, which is the default of [`Config`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RustGenerator;
+
+impl Generator for RustGenerator {
+    fn file_extension(&self) -> &str {
+        "rs"
+    }
+
+    fn generate(
+        &self,
+        schema: &Schema,
+        colfer: &Colfer,
+        config: &Config,
+    ) -> anyhow::Result<String> {
+        generate(schema, colfer, config)
+    }
+
+    fn generate_include_file(&self, schema: &Schema) -> anyhow::Result<Option<String>> {
+        let modules = schema.packages.iter().map(|colfer| {
+            let package = format_ident!("{}", colfer.package);
+            let path = format!("{}.rs", colfer.package.to_snake());
+            quote! {
+                pub mod #package {
+                    include!(#path);
+                }
+            }
+        });
+        let file = syn::parse2::<syn::File>(quote!(#(#modules)*))
+            .context("Generated an invalid include file.")?;
+        Ok(Some(prettyplease::unparse(&file)))
+    }
+}
+
+/// Generates the module of the package `colfer` of `schema`, pretty-printed.
+fn generate(schema: &Schema, colfer: &Colfer, config: &Config) -> anyhow::Result<String> {
+    let mut items = TokenStream::new();
+    for hooks in &config.code_generators {
+        items.extend(hook_items(|code| hooks.prelude(colfer, code))?);
+    }
+
+    for s in &colfer.structs {
+        items.extend(generate_struct(schema, colfer, config, s)?);
+        if config.views {
+            items.extend(generate_view(schema, colfer, config, s)?);
+        }
+        for hooks in &config.code_generators {
+            items.extend(hook_items(|code| hooks.generate_struct(colfer, s, code))?);
+        }
+    }
+
+    for hooks in &config.code_generators {
+        items.extend(hook_items(|code| hooks.epilogue(colfer, code))?);
+    }
+
+    let file = syn::parse2::<syn::File>(items)
+        .with_context(|| format!("Generated invalid code for package `{}`.", colfer.package))?;
+    Ok(prettyplease::unparse(&file))
+}
+
+fn generate_struct(
+    schema: &Schema,
+    colfer: &Colfer,
+    config: &Config,
+    s: &Struct,
+) -> anyhow::Result<TokenStream> {
+    let name = format_ident!("{}", s.name);
+    let struct_name = &s.name;
+    let docs = doc_attributes(&s.docs);
+    let vis = visibility(config)?;
+
+    let mut derives = vec![
+        quote!(Default),
+        quote!(Clone),
+        quote!(Debug),
+        quote!(PartialEq),
+    ];
+    let has_float = schema.has_float(&colfer.package, &s.name);
+    if config.derive_eq && !has_float {
+        derives.push(quote!(Eq));
+    }
+    if config.derive_hash && !has_float {
+        derives.push(quote!(Hash));
+    }
+    if config.derive_partial_ord {
+        derives.push(quote!(PartialOrd));
+    }
+    if config.serde {
+        derives.push(quote!(colfer::serde::Serialize));
+        derives.push(quote!(colfer::serde::Deserialize));
+    }
+    let serde = if config.serde {
+        quote!(#[serde(crate = "colfer::serde", default)])
+    } else {
+        TokenStream::new()
+    };
+    let mut attributes = Vec::new();
+    for attribute in config.type_attributes(colfer, s) {
+        attributes.extend(
+            parse_attributes(attribute)
+                .with_context(|| format!("Invalid attribute `{}` of `{}`.", attribute, s.name))?,
+        );
+    }
+
+    let mut fields = Vec::new();
+    let mut shapes = Vec::new();
+    for f in &s.fields {
+        let field = field_ident(f);
+        let docs = doc_attributes(&f.docs);
+        let rename = if config.serde && f.name.trim_start_matches("r#") != f.schema_name {
+            let schema_name = &f.schema_name;
+            quote!(#[serde(rename = #schema_name)])
+        } else {
+            TokenStream::new()
+        };
+        let mut attributes = Vec::new();
+        for attribute in config.field_attributes(colfer, s, f) {
+            attributes.extend(parse_attributes(attribute).with_context(|| {
+                format!(
+                    "Invalid attribute `{}` of `{}.{}`.",
+                    attribute, s.name, f.schema_name
+                )
+            })?);
+        }
+        let mut hooks = TokenStream::new();
+        for code_generator in &config.code_generators {
+            let mut code = String::new();
+            code_generator.generate_field(colfer, s, f, &mut code)?;
+            let hook_attributes = parse_attributes(&code).with_context(|| {
+                format!(
+                    "Invalid code added to `{}.{}` by a code generator:\n{}",
+                    s.name, f.schema_name, code
+                )
+            })?;
+            hooks.extend(quote!(#(#hook_attributes)*));
+        }
+        let ty = field_type(schema, colfer, s, f, config)?;
+        shapes.push(match &f.ty {
+            FieldType::Struct(name) => {
+                let path = type_path(name);
+                quote!(colfer::Shape::Message(<#path as colfer::Message>::shapes))
+            }
+            FieldType::ArrayStruct(name) => {
+                let path = type_path(name);
+                quote!(colfer::Shape::Messages(<#path as colfer::Message>::shapes))
+            }
+            _ => quote!(<#ty as colfer::Type>::SHAPE),
+        });
+        fields.push(quote! {
+            #docs
+            #rename
+            #(#attributes)*
+            #hooks
+            #vis #field: #ty
+        });
+    }
+    if config.retain_unknown_fields {
+        let skip = if config.serde {
+            quote!(#[serde(skip)])
+        } else {
+            TokenStream::new()
+        };
+        fields.push(quote! {
+            #skip
+            #vis unknown_fields: Vec<u8>
+        });
+    }
+
+    let mut encode = TokenStream::new();
+    let mut size = Vec::new();
+    for (idx, f) in s.fields.iter().enumerate() {
+        let field = field_ident(f);
+        let idx = Literal::usize_unsuffixed(idx);
+        match &f.ty {
+            FieldType::Struct(name) => {
+                let as_ref = if schema.need_box(&colfer.package, &s.name, name)
+                    || config.mapped_type(colfer, s, f).is_some()
+                {
+                    quote!(as_deref)
+                } else {
+                    quote!(as_ref)
+                };
+                encode.extend(quote!(colfer::encode_message(w, #idx, self.#field.#as_ref())?;));
+                size.push(quote!(colfer::message_size(self.#field.#as_ref())));
+            }
+            FieldType::ArrayStruct(_) => {
+                encode.extend(quote!(colfer::encode_messages(w, #idx, &self.#field)?;));
+                size.push(quote!(colfer::messages_size(&self.#field)));
+            }
+            _ => {
+                encode.extend(quote!(colfer::Type::encode(&self.#field, w, #idx)?;));
+                size.push(quote!(colfer::Type::size(&self.#field)));
+            }
+        }
+    }
+    if config.retain_unknown_fields {
+        encode.extend(quote!(std::io::Write::write_all(w, &self.unknown_fields)?;));
+        size.push(quote!(self.unknown_fields.len()));
+    }
+
+    let decode_values = decode_values(schema, colfer, config, s);
+    let field_count = Literal::usize_unsuffixed(s.fields.len());
+    let check_end = |id: TokenStream| quote!(colfer::check_end(#id, #field_count).map_err(|err| err.with_struct(#struct_name))?;);
+
+    let mut decode_known = TokenStream::new();
+    let decode_fields = if config.lenient() {
+        let chain = decode_chain(quote!(r), struct_name, &decode_values, true);
+        decode_known = quote! {
+            impl #name {
+                #[inline]
+                fn decode_known<R: std::io::Read>(
+                    r: &mut R,
+                    limits: &colfer::DecodeLimits,
+                ) -> colfer::Result<(Self, u8)> {
+                    #chain
+                    Ok((obj, header.0))
+                }
+            }
+        };
+        let check_end = check_end(quote!(id));
+        quote! {
+            let (obj, id) = Self::decode_known(r, limits)?;
+            #check_end
+            Ok(obj)
+        }
+    } else if config.strict {
+        let chain = decode_chain(quote!(r), struct_name, &decode_values, true);
+        let check_end = check_end(quote!(header.0));
+        quote! {
+            #chain
+            #check_end
+            Ok(obj)
+        }
+    } else {
+        let chain = decode_chain(quote!(r), struct_name, &decode_values, false);
+        quote! {
+            #chain
+            Ok(obj)
+        }
+    };
+
+    let from_bytes = if config.strict {
+        quote! {
+            #[inline]
+            fn from_bytes_with_limits(
+                data: &[u8],
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                colfer::from_bytes_exact(data, limits)
+            }
+        }
+    } else if config.retain_unknown_fields {
+        quote! {
+            #[inline]
+            fn from_bytes_with_limits(
+                data: &[u8],
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                let (mut obj, unknown_fields) =
+                    colfer::from_bytes_unknown(data, limits, Self::decode_known)?;
+                obj.unknown_fields = unknown_fields.to_vec();
+                Ok(obj)
+            }
+        }
+    } else if config.lenient() {
+        quote! {
+            #[inline]
+            fn from_bytes_with_limits(
+                data: &[u8],
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                colfer::from_bytes_unknown(data, limits, Self::decode_known).map(|(obj, _)| obj)
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        #docs
+        #[derive(#(#derives),*)]
+        #serde
+        #(#attributes)*
+        pub struct #name {
+            #(#fields,)*
+        }
+
+        #decode_known
+
+        impl colfer::Message for #name {
+            #[inline]
+            fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+                #encode
+                colfer::write_end(w)?;
+                Ok(())
+            }
+
+            #[inline]
+            fn decode_fields<R: std::io::Read>(
+                r: &mut R,
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                #decode_fields
+            }
+
+            #from_bytes
+
+            #[inline]
+            fn size(&self) -> usize {
+                let mut size = 1;
+                #(size += #size;)*
+                size
+            }
+
+            #[inline]
+            fn shapes() -> Option<&'static [colfer::Shape]> {
+                const SHAPES: &[colfer::Shape] = &[#(#shapes),*];
+                Some(SHAPES)
+            }
+        }
+    })
+}
+
+fn generate_view(
+    schema: &Schema,
+    colfer: &Colfer,
+    config: &Config,
+    s: &Struct,
+) -> anyhow::Result<TokenStream> {
+    let name = format_ident!("{}View", s.name);
+    let struct_name = &s.name;
+    let vis = visibility(config)?;
+
+    let mut fields = Vec::new();
+    let mut decode_values = Vec::new();
+    for f in &s.fields {
+        let field = field_ident(f);
+        let ty = match &f.ty {
+            FieldType::Bool => quote!(bool),
+            FieldType::U8 => quote!(u8),
+            FieldType::U16 => quote!(u16),
+            FieldType::U32 => quote!(u32),
+            FieldType::U64 => quote!(u64),
+            FieldType::I8 => quote!(i8),
+            FieldType::I16 => quote!(i16),
+            FieldType::I32 => quote!(i32),
+            FieldType::I64 => quote!(i64),
+            FieldType::F32 => quote!(f32),
+            FieldType::F64 => quote!(f64),
+            FieldType::Timestamp => quote!(colfer::DateTime),
+            FieldType::Text => quote!(&'a str),
+            FieldType::Binary => quote!(&'a [u8]),
+            FieldType::Opaque8 => quote!([u8; 1]),
+            FieldType::Opaque16 => quote!([u8; 2]),
+            FieldType::Opaque32 => quote!([u8; 4]),
+            FieldType::Opaque64 => quote!([u8; 8]),
+            FieldType::Struct(name) => {
+                let view = type_path(&format!("{}View", name));
+                if schema.need_box(&colfer.package, &s.name, name) {
+                    quote!(Option<Box<#view<'a>>>)
+                } else {
+                    quote!(Option<#view<'a>>)
+                }
+            }
+            FieldType::ArrayU16 => quote!(colfer::ListView<'a, u16>),
+            FieldType::ArrayU32 => quote!(colfer::ListView<'a, u32>),
+            FieldType::ArrayU64 => quote!(colfer::ListView<'a, u64>),
+            FieldType::ArrayI8 => quote!(colfer::ListView<'a, i8>),
+            FieldType::ArrayI16 => quote!(colfer::ListView<'a, i16>),
+            FieldType::ArrayI32 => quote!(colfer::ListView<'a, i32>),
+            FieldType::ArrayI64 => quote!(colfer::ListView<'a, i64>),
+            FieldType::ArrayF32 => quote!(colfer::ListView<'a, f32>),
+            FieldType::ArrayF64 => quote!(colfer::ListView<'a, f64>),
+            FieldType::ArrayText => quote!(colfer::ListView<'a, &'a str>),
+            FieldType::ArrayBinary => quote!(colfer::ListView<'a, &'a [u8]>),
+            FieldType::ArrayStruct(name) => {
+                let view = type_path(&format!("{}View", name));
+                quote!(colfer::ListView<'a, #view<'a>>)
+            }
+        };
+        fields.push(quote!(#vis #field: #ty));
+
+        let decode = match &f.ty {
+            FieldType::Struct(_) => quote!(colfer::decode_message_view(buf, limits)),
+            _ => quote!(colfer::TypeView::decode_view(buf, header.1, limits)),
+        };
+        let value = with_field(decode, s, f);
+        let value = match &f.ty {
+            FieldType::Struct(name) if schema.need_box(&colfer.package, &s.name, name) => {
+                quote!(Some(Box::new(#value)))
+            }
+            FieldType::Struct(_) => quote!(Some(#value)),
+            _ => value,
+        };
+        decode_values.push(quote!(obj.#field = #value;));
+    }
+
+    let decode_fields = if config.strict {
+        let chain = decode_chain(quote!(buf), struct_name, &decode_values, true);
+        let field_count = Literal::usize_unsuffixed(s.fields.len());
+        quote! {
+            #chain
+            colfer::check_end(header.0, #field_count).map_err(|err| err.with_struct(#struct_name))?;
+            Ok(obj)
+        }
+    } else {
+        let chain = decode_chain(quote!(buf), struct_name, &decode_values, false);
+        quote! {
+            #chain
+            Ok(obj)
+        }
+    };
+    let from_bytes = if config.strict {
+        quote! {
+            #[inline]
+            fn from_bytes_with_limits(
+                data: &'a [u8],
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                colfer::view_from_bytes_exact(data, limits)
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        #[derive(Default, Clone, Debug, PartialEq)]
+        pub struct #name<'a> {
+            #(#fields,)*
+        }
+
+        impl<'a> colfer::MessageView<'a> for #name<'a> {
+            #[inline]
+            fn decode_fields(
+                buf: &mut &'a [u8],
+                limits: &colfer::DecodeLimits,
+            ) -> colfer::Result<Self> {
+                #decode_fields
+            }
+
+            #from_bytes
+        }
+    })
+}
+
+/// Returns the statements that decode the fields of a struct into `obj`, which assign the
+/// field values to `obj` given the header of the field in `header`. The header following the
+/// known fields is left in `header` if `keep_header`.
+fn decode_chain(
+    reader: TokenStream,
+    struct_name: &str,
+    values: &[TokenStream],
+    keep_header: bool,
+) -> TokenStream {
+    let read_header =
+        quote!(colfer::read_header(#reader).map_err(|err| err.with_struct(#struct_name))?);
+    // The last header is only read to consume it, unless it's kept.
+    let reassigned = if keep_header {
+        values.len()
+    } else {
+        values.len().saturating_sub(1)
+    };
+    let header = if reassigned == 0 {
+        quote!(let header = #read_header;)
+    } else {
+        quote!(let mut header = #read_header;)
+    };
+
+    let mut chain = quote! {
+        let mut obj = Self::default();
+        #header
+    };
+    for (idx, value) in values.iter().enumerate() {
+        let next = if idx < reassigned {
+            quote!(header = #read_header;)
+        } else {
+            quote!(#read_header;)
+        };
+        let idx = Literal::usize_unsuffixed(idx);
+        chain.extend(quote! {
+            if header.0 == #idx {
+                #value
+                #next
+            }
+        });
+    }
+    chain
+}
+
+/// Returns the statements that decode field values of struct `s` from `r` into `obj`.
+fn decode_values(
+    schema: &Schema,
+    colfer: &Colfer,
+    config: &Config,
+    s: &Struct,
+) -> Vec<TokenStream> {
+    s.fields
+        .iter()
+        .map(|f| {
+            let mapped = config.mapped_type(colfer, s, f).is_some();
+            let decode = match &f.ty {
+                FieldType::Struct(name) | FieldType::ArrayStruct(name) => {
+                    let func = if let FieldType::Struct(_) = &f.ty {
+                        quote!(decode_message)
+                    } else {
+                        quote!(decode_messages)
+                    };
+                    if mapped {
+                        // The struct can't be inferred through `.into()`.
+                        let path = type_path(name);
+                        quote!(colfer::#func::<_, #path>(r, limits))
+                    } else {
+                        quote!(colfer::#func(r, limits))
+                    }
+                }
+                _ => quote!(colfer::Type::decode(r, header.1, limits)),
+            };
+            let value = with_field(decode, s, f);
+            let value = match &f.ty {
+                FieldType::Struct(_) if mapped => quote!(Some(#value.into())),
+                FieldType::Struct(name) if schema.need_box(&colfer.package, &s.name, name) => {
+                    quote!(Some(Box::new(#value)))
+                }
+                FieldType::Struct(_) => quote!(Some(#value)),
+                FieldType::ArrayStruct(_) if mapped => quote!(#value.into()),
+                _ => value,
+            };
+            let field = field_ident(f);
+            quote!(obj.#field = #value;)
+        })
+        .collect()
+}
+
+/// Returns the expression `decode`, adding field `f` of struct `s` to its error.
+fn with_field(decode: TokenStream, s: &Struct, f: &Field) -> TokenStream {
+    let struct_name = &s.name;
+    let field_name = f.name.trim_start_matches("r#");
+    quote!(#decode.map_err(|err| err.with_field(#struct_name, #field_name))?)
+}
+
+/// Returns the Rust type of a struct field, which is either configured with
+/// [`Config::map_type`] or [`Config::map_field`], or the default for its schema type.
+fn field_type(
+    schema: &Schema,
+    colfer: &Colfer,
+    s: &Struct,
+    f: &Field,
+    config: &Config,
+) -> anyhow::Result<TokenStream> {
+    let element = match &f.ty {
+        FieldType::ArrayU16 => Some(quote!(u16)),
+        FieldType::ArrayU32 => Some(quote!(u32)),
+        FieldType::ArrayU64 => Some(quote!(u64)),
+        FieldType::ArrayI8 => Some(quote!(i8)),
+        FieldType::ArrayI16 => Some(quote!(i16)),
+        FieldType::ArrayI32 => Some(quote!(i32)),
+        FieldType::ArrayI64 => Some(quote!(i64)),
+        FieldType::ArrayF32 => Some(quote!(f32)),
+        FieldType::ArrayF64 => Some(quote!(f64)),
+        FieldType::ArrayText => Some(quote!(String)),
+        FieldType::ArrayBinary => Some(quote!(Vec<u8>)),
+        FieldType::ArrayStruct(name) => Some(type_path(name)),
+        _ => None,
+    };
+    let ty = match &f.ty {
+        FieldType::Bool => quote!(bool),
+        FieldType::U8 => quote!(u8),
+        FieldType::U16 => quote!(u16),
+        FieldType::U32 => quote!(u32),
+        FieldType::U64 => quote!(u64),
+        FieldType::I8 => quote!(i8),
+        FieldType::I16 => quote!(i16),
+        FieldType::I32 => quote!(i32),
+        FieldType::I64 => quote!(i64),
+        FieldType::F32 => quote!(f32),
+        FieldType::F64 => quote!(f64),
+        FieldType::Timestamp => quote!(colfer::DateTime),
+        FieldType::Text => quote!(String),
+        FieldType::Binary => quote!(Vec<u8>),
+        FieldType::Opaque8 => quote!([u8; 1]),
+        FieldType::Opaque16 => quote!([u8; 2]),
+        FieldType::Opaque32 => quote!([u8; 4]),
+        FieldType::Opaque64 => quote!([u8; 8]),
+        FieldType::Struct(name) => {
+            let path = type_path(name);
+            if schema.need_box(&colfer.package, &s.name, name) {
+                quote!(Option<Box<#path>>)
+            } else {
+                quote!(Option<#path>)
+            }
+        }
+        _ => quote!(Vec<#element>),
+    };
+
+    match config.mapped_type(colfer, s, f) {
+        Some(mapped) => {
+            // `{}` stands for the element type of a list.
+            let mapped = match &element {
+                Some(element) => mapped.replace("{}", &element.to_string()),
+                None => mapped.to_string(),
+            };
+            let ty = syn::parse_str::<syn::Type>(&mapped).with_context(|| {
+                format!(
+                    "Invalid Rust type `{}` of `{}.{}`.",
+                    mapped, s.name, f.schema_name
+                )
+            })?;
+            Ok(ty.into_token_stream())
+        }
+        None => Ok(ty),
+    }
+}
+
+/// Returns the Rust path of the struct referenced as `name`, which is `super::package::Name`
+/// for a struct of another package.
+fn type_path(name: &str) -> TokenStream {
+    match name.split_once('.') {
+        Some((package, name)) => {
+            let package = format_ident!("{}", package);
+            let name = format_ident!("{}", name);
+            quote!(super::#package::#name)
+        }
+        None => format_ident!("{}", name).into_token_stream(),
+    }
+}
+
+fn field_ident(f: &Field) -> Ident {
+    match f.name.strip_prefix("r#") {
+        Some(name) => Ident::new_raw(name, Span::call_site()),
+        None => Ident::new(&f.name, Span::call_site()),
+    }
+}
+
+fn visibility(config: &Config) -> anyhow::Result<syn::Visibility> {
+    let visibility = config.generated_field_visibility();
+    syn::parse_str(visibility)
+        .with_context(|| format!("Invalid field visibility `{}`.", visibility))
+}
+
+fn doc_attributes(docs: &[String]) -> TokenStream {
+    let lines = docs.iter().map(|line| {
+        if line.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", line)
+        }
+    });
+    quote!(#(#[doc = #lines])*)
+}
+
+fn parse_attributes(code: &str) -> syn::Result<Vec<syn::Attribute>> {
+    syn::Attribute::parse_outer.parse_str(code)
+}
+
+/// Returns the items added by a hook of a [`CodeGenerator`](super::CodeGenerator).
+fn hook_items(hook: impl FnOnce(&mut String) -> anyhow::Result<()>) -> anyhow::Result<TokenStream> {
+    let mut code = String::new();
+    hook(&mut code)?;
+    let parse_items = |input: ParseStream| {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse::<syn::Item>()?);
+        }
+        Ok(items)
+    };
+    let items = parse_items
+        .parse_str(&code)
+        .with_context(|| format!("Invalid code added by a code generator:\n{}", code))?;
+    Ok(quote!(#(#items)*))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CodeGenerator;
+
+    struct Invalid;
+
+    impl CodeGenerator for Invalid {
+        fn epilogue(&self, _colfer: &Colfer, code: &mut String) -> anyhow::Result<()> {
+            code.push_str("impl A {");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let schema = Schema::new(vec![crate::parse_schema(
+            "package demo
+type a struct {
+	b bool
+	c a
+}
+",
+        )
+        .unwrap()]);
+        let colfer = &schema.packages[0];
+        let code = generate(&schema, colfer, &Config::default()).unwrap();
+        for line in [
+            "pub struct A {\n    pub b: bool,\n    pub c: Option<Box<A>>,\n}",
+            "        colfer::Type::encode(&self.b, w, 0)?;",
+            "        let mut header = colfer::read_header(r).map_err(|err| err.with_struct(\"A\"))?;",
+            "            colfer::read_header(r).map_err(|err| err.with_struct(\"A\"))?;\n        }\n        Ok(obj)",
+        ] {
+            assert!(code.contains(line), "missing `{}` in:\n{}", line, code);
+        }
+        assert!(!code.contains("#!["));
+        assert_eq!(
+            RustGenerator
+                .generate_include_file(&schema)
+                .unwrap()
+                .unwrap(),
+            "pub mod demo {\n    include!(\"demo.rs\");\n}\n"
+        );
+
+        let config = Config::default().type_attribute(".", "#[derive(");
+        assert!(generate(&schema, colfer, &config).is_err());
+        let config = Config::default().map_type("bool", "Vec<");
+        assert!(generate(&schema, colfer, &config).is_err());
+        let config = Config::default().code_generator(Invalid);
+        assert!(generate(&schema, colfer, &config).is_err());
+    }
+}
@@ -0,0 +1,695 @@
+use std::fmt::Write;
+
+use case::CaseExt;
+
+use super::Generator;
+use crate::ast::{Colfer, FieldType, Schema};
+use crate::Config;
+
+/// Names of the generated class members, which fields must not shadow.
+const RESERVED_FIELDS: &[&str] = &["constructor", "encode", "marshal"];
+
+/// The encoding and decoding helpers, which every generated module contains.
+const RUNTIME: &str = r#"/** A timestamp with nanosecond precision. */
+export interface DateTime {
+	/** Seconds since January 1, 1970 UTC. */
+	seconds: bigint;
+	/** Nanoseconds since the last second boundary. */
+	nanos: number;
+}
+
+function checkRange(x: number, min: number, max: number): number {
+	if (!Number.isInteger(x) || x < min || x > max) {
+		throw new RangeError(`colfer: ${x} is out of range`);
+	}
+	return x;
+}
+
+function checkBigRange(x: bigint, min: bigint, max: bigint): bigint {
+	if (x < min || x > max) {
+		throw new RangeError(`colfer: ${x} is out of range`);
+	}
+	return x;
+}
+
+function signedMin(bits: bigint): bigint {
+	return -(1n << (bits - 1n));
+}
+
+function signedMax(bits: bigint): bigint {
+	return (1n << (bits - 1n)) - 1n;
+}
+
+/** @internal */
+export class ColferWriter {
+	buf = new Uint8Array(256);
+	len = 0;
+
+	finish(): Uint8Array {
+		return this.buf.slice(0, this.len);
+	}
+
+	reserve(n: number): DataView {
+		if (this.len + n > this.buf.length) {
+			const buf = new Uint8Array(Math.max(this.buf.length * 2, this.len + n));
+			buf.set(this.buf.subarray(0, this.len));
+			this.buf = buf;
+		}
+		const view = new DataView(this.buf.buffer, this.len, n);
+		this.len += n;
+		return view;
+	}
+
+	byte(b: number): void {
+		this.reserve(1).setUint8(0, b);
+	}
+
+	raw(data: Uint8Array): void {
+		const start = this.len;
+		this.reserve(data.length);
+		this.buf.set(data, start);
+	}
+
+	varint(x: bigint): void {
+		for (let i = 0; i < 8 && x >= 0x80n; i++) {
+			this.byte(Number(x & 0x7fn) | 0x80);
+			x >>= 7n;
+		}
+		this.byte(Number(x));
+	}
+
+	bool(id: number, x: boolean): void {
+		if (x) {
+			this.byte(id);
+		}
+	}
+
+	uint8(id: number, x: number): void {
+		if (checkRange(x, 0, 0xff) !== 0) {
+			this.byte(id);
+			this.byte(x);
+		}
+	}
+
+	uint16(id: number, x: number): void {
+		if (checkRange(x, 0, 0xffff) >= 0x100) {
+			this.byte(id);
+			this.reserve(2).setUint16(0, x);
+		} else if (x !== 0) {
+			this.byte(id | 0x80);
+			this.byte(x);
+		}
+	}
+
+	uint32(id: number, x: number): void {
+		if (checkRange(x, 0, 0xffffffff) >= 1 << 21) {
+			this.byte(id | 0x80);
+			this.reserve(4).setUint32(0, x);
+		} else if (x !== 0) {
+			this.byte(id);
+			this.varint(BigInt(x));
+		}
+	}
+
+	uint64(id: number, x: bigint): void {
+		if (checkBigRange(x, 0n, (1n << 64n) - 1n) >= 1n << 49n) {
+			this.byte(id | 0x80);
+			this.reserve(8).setBigUint64(0, x);
+		} else if (x !== 0n) {
+			this.byte(id);
+			this.varint(x);
+		}
+	}
+
+	int(id: number, x: bigint, bits: bigint): void {
+		if (checkBigRange(x, signedMin(bits), signedMax(bits)) > 0n) {
+			this.byte(id);
+			this.varint(x);
+		} else if (x < 0n) {
+			this.byte(id | 0x80);
+			this.varint(-x);
+		}
+	}
+
+	float32(id: number, x: number): void {
+		if (x !== 0) {
+			this.byte(id);
+			this.reserve(4).setFloat32(0, x);
+		}
+	}
+
+	float64(id: number, x: number): void {
+		if (x !== 0) {
+			this.byte(id);
+			this.reserve(8).setFloat64(0, x);
+		}
+	}
+
+	timestamp(id: number, x: DateTime): void {
+		checkBigRange(x.seconds, signedMin(64n), signedMax(64n));
+		checkRange(x.nanos, 0, 999999999);
+		if (x.seconds === 0n && x.nanos === 0) {
+			return;
+		}
+		if (x.seconds >= 0n && x.seconds < 1n << 32n) {
+			this.byte(id);
+			this.reserve(4).setUint32(0, Number(x.seconds));
+		} else {
+			this.byte(id | 0x80);
+			this.reserve(8).setBigInt64(0, x.seconds);
+		}
+		this.reserve(4).setUint32(0, x.nanos);
+	}
+
+	text(id: number, x: string): void {
+		if (x.length !== 0) {
+			this.byte(id);
+			this.textElement(x);
+		}
+	}
+
+	binary(id: number, x: Uint8Array): void {
+		if (x.length !== 0) {
+			this.byte(id);
+			this.binaryElement(x);
+		}
+	}
+
+	opaque(id: number, x: Uint8Array, size: number): void {
+		if (x.length !== size) {
+			throw new RangeError(`colfer: opaque data of ${x.length} bytes instead of ${size}`);
+		}
+		if (x.some((b) => b !== 0)) {
+			this.byte(id);
+			this.raw(x);
+		}
+	}
+
+	message(id: number, x: { encode(w: ColferWriter): void } | null): void {
+		if (x !== null) {
+			this.byte(id);
+			x.encode(this);
+		}
+	}
+
+	list<T>(id: number, xs: T[], write: (x: T) => void): void {
+		if (xs.length !== 0) {
+			this.byte(id);
+			this.varint(BigInt(xs.length));
+			xs.forEach(write);
+		}
+	}
+
+	unsignedElement(x: bigint, bits: bigint): void {
+		checkBigRange(x, 0n, (1n << bits) - 1n);
+		const view = this.reserve(Number(bits / 8n));
+		for (let i = view.byteLength - 1; i >= 0; i--, x >>= 8n) {
+			view.setUint8(i, Number(x & 0xffn));
+		}
+	}
+
+	signedElement(x: bigint, bits: bigint): void {
+		checkBigRange(x, signedMin(bits), signedMax(bits));
+		this.unsignedElement(BigInt.asUintN(Number(bits), x), bits);
+	}
+
+	float32Element(x: number): void {
+		this.reserve(4).setFloat32(0, x);
+	}
+
+	float64Element(x: number): void {
+		this.reserve(8).setFloat64(0, x);
+	}
+
+	textElement(x: string): void {
+		this.binaryElement(new TextEncoder().encode(x));
+	}
+
+	binaryElement(x: Uint8Array): void {
+		this.varint(BigInt(x.length));
+		this.raw(x);
+	}
+}
+
+/** @internal */
+export class ColferReader {
+	data: Uint8Array;
+	pos = 0;
+
+	constructor(data: Uint8Array) {
+		this.data = data;
+	}
+
+	take(n: number): DataView {
+		if (n > this.data.length - this.pos) {
+			throw new Error("colfer: data is truncated");
+		}
+		const view = new DataView(this.data.buffer, this.data.byteOffset + this.pos, n);
+		this.pos += n;
+		return view;
+	}
+
+	byte(): number {
+		return this.take(1).getUint8(0);
+	}
+
+	varint(): bigint {
+		let b = this.byte();
+		if (b < 0x80) {
+			return BigInt(b);
+		}
+		let x = BigInt(b & 0x7f);
+		for (let shift = 7n; ; shift += 7n) {
+			b = this.byte();
+			if (b < 0x80 || shift === 56n) {
+				return x | (BigInt(b) << shift);
+			}
+			x |= BigInt(b & 0x7f) << shift;
+		}
+	}
+
+	length(): number {
+		const n = this.varint();
+		if (n > BigInt(this.data.length - this.pos)) {
+			throw new Error("colfer: data is truncated");
+		}
+		return Number(n);
+	}
+
+	header(): [number, boolean] {
+		const b = this.byte();
+		return [b & 0x7f, (b & 0x80) !== 0];
+	}
+
+	uint8(): number {
+		return this.byte();
+	}
+
+	uint16(flag: boolean): number {
+		return flag ? this.byte() : this.take(2).getUint16(0);
+	}
+
+	uint32(flag: boolean): number {
+		return flag
+			? this.take(4).getUint32(0)
+			: Number(this.checkOverflow(this.varint(), 0n, (1n << 32n) - 1n));
+	}
+
+	uint64(flag: boolean): bigint {
+		return flag ? this.take(8).getBigUint64(0) : this.varint();
+	}
+
+	int(flag: boolean, bits: bigint): bigint {
+		const x = flag ? -this.varint() : this.varint();
+		return this.checkOverflow(x, signedMin(bits), signedMax(bits));
+	}
+
+	float32(): number {
+		return this.take(4).getFloat32(0);
+	}
+
+	float64(): number {
+		return this.take(8).getFloat64(0);
+	}
+
+	timestamp(flag: boolean): DateTime {
+		const seconds = flag ? this.take(8).getBigInt64(0) : BigInt(this.take(4).getUint32(0));
+		return { seconds, nanos: this.take(4).getUint32(0) };
+	}
+
+	text(): string {
+		return new TextDecoder("utf-8", { fatal: true }).decode(this.binary());
+	}
+
+	binary(): Uint8Array {
+		return this.opaque(this.length());
+	}
+
+	opaque(size: number): Uint8Array {
+		this.take(size);
+		return this.data.slice(this.pos - size, this.pos);
+	}
+
+	list<T>(read: () => T): T[] {
+		const n = this.length();
+		const xs: T[] = [];
+		for (let i = 0; i < n; i++) {
+			xs.push(read());
+		}
+		return xs;
+	}
+
+	unsignedElement(bits: bigint): bigint {
+		const view = this.take(Number(bits / 8n));
+		let x = 0n;
+		for (let i = 0; i < view.byteLength; i++) {
+			x = (x << 8n) | BigInt(view.getUint8(i));
+		}
+		return x;
+	}
+
+	signedElement(bits: bigint): bigint {
+		return BigInt.asIntN(Number(bits), this.unsignedElement(bits));
+	}
+
+	checkOverflow(x: bigint, min: bigint, max: bigint): bigint {
+		if (x < min || x > max) {
+			throw new Error("colfer: integer overflow");
+		}
+		return x;
+	}
+}
+"#;
+
+/// Generates TypeScript modules, with a class per struct that encodes with `marshal()` and
+/// decodes with the static `unmarshal(data)`.
+///
+/// 64-bit integers are `bigint`s, timestamps are `DateTime` objects of `bigint` seconds and
+/// nanoseconds, and binaries and opaque data are `Uint8Array`s. The structs of another
+/// package are imported from the module of that package, which must be next to the module.
+/// The options of [`Config`] don't apply.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TypeScriptGenerator;
+
+impl Generator for TypeScriptGenerator {
+    fn file_extension(&self) -> &str {
+        "ts"
+    }
+
+    fn generate(
+        &self,
+        _schema: &Schema,
+        colfer: &Colfer,
+        _config: &Config,
+    ) -> anyhow::Result<String> {
+        generate(colfer)
+    }
+}
+
+fn generate(colfer: &Colfer) -> anyhow::Result<String> {
+    let mut code = String::new();
+    writeln!(
+        &mut code,
+        "// Code generated by colfer-build from package {}. DO NOT EDIT.",
+        colfer.package
+    )?;
+    writeln!(&mut code)?;
+
+    let mut imports: Vec<&str> = colfer
+        .structs
+        .iter()
+        .flat_map(|s| &s.fields)
+        .filter_map(|f| match &f.ty {
+            FieldType::Struct(name) | FieldType::ArrayStruct(name) => {
+                name.split_once('.').map(|(package, _)| package)
+            }
+            _ => None,
+        })
+        .collect();
+    imports.sort_unstable();
+    imports.dedup();
+    for package in &imports {
+        writeln!(
+            &mut code,
+            "import * as {} from \"./{}\";",
+            package,
+            package.to_snake()
+        )?;
+    }
+    if !imports.is_empty() {
+        writeln!(&mut code)?;
+    }
+    writeln!(&mut code, "{}", RUNTIME)?;
+
+    for s in &colfer.structs {
+        write_docs(&mut code, "", &s.docs)?;
+        writeln!(&mut code, "export class {} {{", s.name)?;
+        for f in &s.fields {
+            if RESERVED_FIELDS.contains(&f.schema_name.as_str()) {
+                anyhow::bail!(
+                    "Field `{}` of struct `{}` conflicts with a generated method.",
+                    f.schema_name,
+                    s.name
+                );
+            }
+            let (ty, default) = field_type(&f.ty);
+            write_docs(&mut code, "\t", &f.docs)?;
+            writeln!(&mut code, "\t{}: {} = {};", f.schema_name, ty, default)?;
+        }
+        if !s.fields.is_empty() {
+            writeln!(&mut code)?;
+        }
+
+        writeln!(&mut code, "\t/** Returns the encoding of the message. */")?;
+        writeln!(&mut code, "\tmarshal(): Uint8Array {{")?;
+        writeln!(&mut code, "\t\tconst w = new ColferWriter();")?;
+        writeln!(&mut code, "\t\tthis.encode(w);")?;
+        writeln!(&mut code, "\t\treturn w.finish();")?;
+        writeln!(&mut code, "\t}}")?;
+        writeln!(&mut code)?;
+
+        writeln!(
+            &mut code,
+            "\t/** Decodes a message from the start of `data`. */"
+        )?;
+        writeln!(
+            &mut code,
+            "\tstatic unmarshal(data: Uint8Array): {} {{",
+            s.name
+        )?;
+        writeln!(
+            &mut code,
+            "\t\treturn {}.decode(new ColferReader(data));",
+            s.name
+        )?;
+        writeln!(&mut code, "\t}}")?;
+        writeln!(&mut code)?;
+
+        writeln!(&mut code, "\t/** @internal */")?;
+        writeln!(&mut code, "\tencode(w: ColferWriter): void {{")?;
+        for (idx, f) in s.fields.iter().enumerate() {
+            writeln!(
+                &mut code,
+                "\t\t{};",
+                encode(&f.ty, idx, &format!("this.{}", f.schema_name))
+            )?;
+        }
+        writeln!(&mut code, "\t\tw.byte(0x7f);")?;
+        writeln!(&mut code, "\t}}")?;
+        writeln!(&mut code)?;
+
+        writeln!(&mut code, "\t/** @internal */")?;
+        writeln!(&mut code, "\tstatic decode(r: ColferReader): {} {{", s.name)?;
+        writeln!(&mut code, "\t\tconst obj = new {}();", s.name)?;
+        writeln!(&mut code, "\t\tlet [id, flag] = r.header();")?;
+        for (idx, f) in s.fields.iter().enumerate() {
+            writeln!(&mut code, "\t\tif (id === {}) {{", idx)?;
+            writeln!(
+                &mut code,
+                "\t\t\tobj.{} = {};",
+                f.schema_name,
+                decode(&f.ty)
+            )?;
+            writeln!(&mut code, "\t\t\t[id, flag] = r.header();")?;
+            writeln!(&mut code, "\t\t}}")?;
+        }
+        writeln!(&mut code, "\t\tif (id !== 0x7f) {{")?;
+        writeln!(
+            &mut code,
+            "\t\t\tthrow new Error(`colfer: unexpected field ${{id}} in {}`);",
+            s.name
+        )?;
+        writeln!(&mut code, "\t\t}}")?;
+        if !s.fields.iter().any(|f| decode(&f.ty).contains("flag")) {
+            writeln!(&mut code, "\t\tvoid flag;")?;
+        }
+        writeln!(&mut code, "\t\treturn obj;")?;
+        writeln!(&mut code, "\t}}")?;
+        writeln!(&mut code, "}}")?;
+        writeln!(&mut code)?;
+    }
+
+    code.truncate(code.trim_end().len() + 1);
+    Ok(code)
+}
+
+/// Returns the TypeScript type of a field along with its default value.
+fn field_type(ty: &FieldType) -> (String, String) {
+    let (ty, default) = match ty {
+        FieldType::Bool => ("boolean", "false"),
+        FieldType::U8
+        | FieldType::U16
+        | FieldType::U32
+        | FieldType::I8
+        | FieldType::I16
+        | FieldType::I32
+        | FieldType::F32
+        | FieldType::F64 => ("number", "0"),
+        FieldType::U64 | FieldType::I64 => ("bigint", "0n"),
+        FieldType::Timestamp => ("DateTime", "{ seconds: 0n, nanos: 0 }"),
+        FieldType::Text => ("string", "\"\""),
+        FieldType::Binary => ("Uint8Array", "new Uint8Array(0)"),
+        FieldType::Opaque8 => ("Uint8Array", "new Uint8Array(1)"),
+        FieldType::Opaque16 => ("Uint8Array", "new Uint8Array(2)"),
+        FieldType::Opaque32 => ("Uint8Array", "new Uint8Array(4)"),
+        FieldType::Opaque64 => ("Uint8Array", "new Uint8Array(8)"),
+        FieldType::Struct(name) => return (format!("{} | null", name), "null".to_string()),
+        FieldType::ArrayU16
+        | FieldType::ArrayU32
+        | FieldType::ArrayI8
+        | FieldType::ArrayI16
+        | FieldType::ArrayI32
+        | FieldType::ArrayF32
+        | FieldType::ArrayF64 => ("number[]", "[]"),
+        FieldType::ArrayU64 | FieldType::ArrayI64 => ("bigint[]", "[]"),
+        FieldType::ArrayText => ("string[]", "[]"),
+        FieldType::ArrayBinary => ("Uint8Array[]", "[]"),
+        FieldType::ArrayStruct(name) => return (format!("{}[]", name), "[]".to_string()),
+    };
+    (ty.to_string(), default.to_string())
+}
+
+/// Returns the statement that encodes `value` as field `idx` to the writer `w`.
+fn encode(ty: &FieldType, idx: usize, value: &str) -> String {
+    let list = |element: &str| format!("w.list({}, {}, (x) => {})", idx, value, element);
+    match ty {
+        FieldType::Bool => format!("w.bool({}, {})", idx, value),
+        FieldType::U8 => format!("w.uint8({}, {})", idx, value),
+        FieldType::U16 => format!("w.uint16({}, {})", idx, value),
+        FieldType::U32 => format!("w.uint32({}, {})", idx, value),
+        FieldType::U64 => format!("w.uint64({}, {})", idx, value),
+        FieldType::I8 => format!("w.int({}, BigInt({}), 8n)", idx, value),
+        FieldType::I16 => format!("w.int({}, BigInt({}), 16n)", idx, value),
+        FieldType::I32 => format!("w.int({}, BigInt({}), 32n)", idx, value),
+        FieldType::I64 => format!("w.int({}, {}, 64n)", idx, value),
+        FieldType::F32 => format!("w.float32({}, {})", idx, value),
+        FieldType::F64 => format!("w.float64({}, {})", idx, value),
+        FieldType::Timestamp => format!("w.timestamp({}, {})", idx, value),
+        FieldType::Text => format!("w.text({}, {})", idx, value),
+        FieldType::Binary => format!("w.binary({}, {})", idx, value),
+        FieldType::Opaque8 => format!("w.opaque({}, {}, 1)", idx, value),
+        FieldType::Opaque16 => format!("w.opaque({}, {}, 2)", idx, value),
+        FieldType::Opaque32 => format!("w.opaque({}, {}, 4)", idx, value),
+        FieldType::Opaque64 => format!("w.opaque({}, {}, 8)", idx, value),
+        FieldType::Struct(_) => format!("w.message({}, {})", idx, value),
+        FieldType::ArrayU16 => list("w.unsignedElement(BigInt(x), 16n)"),
+        FieldType::ArrayU32 => list("w.unsignedElement(BigInt(x), 32n)"),
+        FieldType::ArrayU64 => list("w.unsignedElement(x, 64n)"),
+        FieldType::ArrayI8 => list("w.signedElement(BigInt(x), 8n)"),
+        FieldType::ArrayI16 => list("w.signedElement(BigInt(x), 16n)"),
+        FieldType::ArrayI32 => list("w.signedElement(BigInt(x), 32n)"),
+        FieldType::ArrayI64 => list("w.signedElement(x, 64n)"),
+        FieldType::ArrayF32 => list("w.float32Element(x)"),
+        FieldType::ArrayF64 => list("w.float64Element(x)"),
+        FieldType::ArrayText => list("w.textElement(x)"),
+        FieldType::ArrayBinary => list("w.binaryElement(x)"),
+        FieldType::ArrayStruct(_) => list("x.encode(w)"),
+    }
+}
+
+/// Returns the expression that decodes a field from the reader `r`, with the header flag
+/// `flag`.
+fn decode(ty: &FieldType) -> String {
+    let list = |element: &str| format!("r.list(() => {})", element);
+    match ty {
+        FieldType::Bool => "true".to_string(),
+        FieldType::U8 => "r.uint8()".to_string(),
+        FieldType::U16 => "r.uint16(flag)".to_string(),
+        FieldType::U32 => "r.uint32(flag)".to_string(),
+        FieldType::U64 => "r.uint64(flag)".to_string(),
+        FieldType::I8 => "Number(r.int(flag, 8n))".to_string(),
+        FieldType::I16 => "Number(r.int(flag, 16n))".to_string(),
+        FieldType::I32 => "Number(r.int(flag, 32n))".to_string(),
+        FieldType::I64 => "r.int(flag, 64n)".to_string(),
+        FieldType::F32 => "r.float32()".to_string(),
+        FieldType::F64 => "r.float64()".to_string(),
+        FieldType::Timestamp => "r.timestamp(flag)".to_string(),
+        FieldType::Text => "r.text()".to_string(),
+        FieldType::Binary => "r.binary()".to_string(),
+        FieldType::Opaque8 => "r.opaque(1)".to_string(),
+        FieldType::Opaque16 => "r.opaque(2)".to_string(),
+        FieldType::Opaque32 => "r.opaque(4)".to_string(),
+        FieldType::Opaque64 => "r.opaque(8)".to_string(),
+        FieldType::Struct(name) => format!("{}.decode(r)", name),
+        FieldType::ArrayU16 => list("Number(r.unsignedElement(16n))"),
+        FieldType::ArrayU32 => list("Number(r.unsignedElement(32n))"),
+        FieldType::ArrayU64 => list("r.unsignedElement(64n)"),
+        FieldType::ArrayI8 => list("Number(r.signedElement(8n))"),
+        FieldType::ArrayI16 => list("Number(r.signedElement(16n))"),
+        FieldType::ArrayI32 => list("Number(r.signedElement(32n))"),
+        FieldType::ArrayI64 => list("r.signedElement(64n)"),
+        FieldType::ArrayF32 => list("r.float32()"),
+        FieldType::ArrayF64 => list("r.float64()"),
+        FieldType::ArrayText => list("r.text()"),
+        FieldType::ArrayBinary => list("r.binary()"),
+        FieldType::ArrayStruct(name) => list(&format!("{}.decode(r)", name)),
+    }
+}
+
+fn write_docs(code: &mut String, indent: &str, docs: &[String]) -> anyhow::Result<()> {
+    // A `*/` in a schema comment would end the doc comment early.
+    let docs: Vec<_> = docs.iter().map(|line| line.replace("*/", "*\\/")).collect();
+    match docs.as_slice() {
+        [] => {}
+        [line] => writeln!(code, "{}/** {} */", indent, line)?,
+        _ => {
+            writeln!(code, "{}/**", indent)?;
+            for line in &docs {
+                writeln!(
+                    code,
+                    "{} *{}{}",
+                    indent,
+                    if line.is_empty() { "" } else { " " },
+                    line
+                )?;
+            }
+            writeln!(code, "{} */", indent)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn test_generate() {
+        let colfer = parse(
+            "package demo
+// A references itself and another package.
+type a struct {
+	// N ends with */ n = 1; /* here.
+	n int32
+	b a
+	ts []people.person
+}
+",
+        )
+        .unwrap();
+        let code = generate(&colfer).unwrap();
+        assert!(code.starts_with(
+            "// Code generated by colfer-build from package demo. DO NOT EDIT.
+
+import * as people from \"./people\";
+"
+        ));
+        for line in [
+            "/** A references itself and another package. */\nexport class A {",
+            "\t/** N ends with *\\/ n = 1; /* here. */\n\tn: number = 0;",
+            "\tb: A | null = null;",
+            "\tts: people.Person[] = [];",
+            "\t\tw.int(0, BigInt(this.n), 32n);",
+            "\t\tw.message(1, this.b);",
+            "\t\tw.list(2, this.ts, (x) => x.encode(w));",
+            "\t\t\tobj.b = A.decode(r);",
+            "\t\t\tobj.ts = r.list(() => people.Person.decode(r));",
+        ] {
+            assert!(code.contains(line), "missing `{}`", line);
+        }
+
+        let colfer = parse("package demo\ntype a struct {\n\tencode bool\n}\n").unwrap();
+        assert!(generate(&colfer).is_err());
+    }
+}
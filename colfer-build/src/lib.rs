@@ -1,6 +1,80 @@
-mod ast;
+use std::path::Path;
+
+use anyhow::Context;
+
+pub mod ast;
+pub mod compat;
 mod config;
+pub mod dynamic;
+mod error;
 mod generator;
 mod parser;
 
 pub use config::Config;
+pub use error::{Diagnostic, SchemaError};
+pub use generator::{CodeGenerator, Generator, RustGenerator, TypeScriptGenerator};
+
+fn parse(file: Option<&Path>, source: &str) -> Result<ast::Colfer, SchemaError> {
+    let colfer = parser::parse(source)
+        .map_err(|diagnostic| SchemaError::new(file, source, vec![diagnostic]))?;
+    colfer
+        .validate()
+        .map_err(|diagnostics| SchemaError::new(file, source, diagnostics))?;
+    Ok(colfer)
+}
+
+/// Parses and validates a colfer schema.
+pub fn parse_schema(source: &str) -> Result<ast::Colfer, SchemaError> {
+    parse(None, source)
+}
+
+/// Reads, parses and validates the colfer schema file `path`.
+pub fn parse_schema_file(path: impl AsRef<Path>) -> anyhow::Result<ast::Colfer> {
+    let path = path.as_ref();
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read `{}`.", path.display()))?;
+    Ok(parse(Some(path), &source)?)
+}
+
+/// Reads, parses and validates the colfer schema `files` together. The files of a package are
+/// merged, and structs may reference the structs of other packages as `package.Name`.
+pub fn parse_schema_files<P: AsRef<Path>>(files: &[P]) -> anyhow::Result<ast::Schema> {
+    let mut sources = Vec::new();
+    for path in files {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read `{}`.", path.display()))?;
+        sources.push((path, source));
+    }
+
+    let mut errors = Vec::new();
+    let mut parsed = Vec::new();
+    for (path, source) in &sources {
+        match parser::parse(source) {
+            Ok(colfer) => parsed.push((*path, source, colfer)),
+            Err(diagnostic) => errors.push(SchemaError::new(Some(path), source, vec![diagnostic])),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(SchemaError::merge(errors).into());
+    }
+
+    for (idx, (path, source, colfer)) in parsed.iter().enumerate() {
+        let others: Vec<_> = parsed
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != idx)
+            .map(|(_, (_, _, colfer))| colfer)
+            .collect();
+        if let Err(diagnostics) = colfer.validate_with(&others) {
+            errors.push(SchemaError::new(Some(path), source, diagnostics));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(SchemaError::merge(errors).into());
+    }
+
+    Ok(ast::Schema::new(
+        parsed.into_iter().map(|(_, _, colfer)| colfer).collect(),
+    ))
+}
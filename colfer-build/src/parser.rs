@@ -1,20 +1,39 @@
 use case::CaseExt;
 use nom::branch::alt;
-use nom::bytes::complete::{is_not, tag};
-use nom::character::complete::{alpha1, alphanumeric1, one_of};
-use nom::combinator::{eof, map, recognize, value};
+use nom::bytes::complete::{tag, take_till};
+use nom::character::complete::{alpha1, alphanumeric1, char, one_of};
+use nom::combinator::{all_consuming, cut, eof, map, not, opt, recognize, value};
 use nom::error::{context, ContextError};
-use nom::error::{ParseError, VerboseError};
+use nom::error::{ParseError, VerboseError, VerboseErrorKind};
 use nom::multi::{fold_many0, many0, many1};
 use nom::sequence::{delimited, pair, preceded, terminated, tuple};
 use nom::IResult;
 
-use crate::ast::{Colfer, Field, FieldType, Struct};
+use crate::ast::{Colfer, Field, FieldType, Span, Struct};
+use crate::error::Diagnostic;
+
+/// Runs `f`, returning its output along with the span of the input it consumed.
+///
+/// The span holds the remaining lengths of the input, as the start of the source is not known
+/// here, and is converted to offsets by `parse`.
+fn spanned<'a, O, E, F>(mut f: F) -> impl FnMut(&'a str) -> IResult<&'a str, (O, Span), E>
+where
+    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+{
+    move |input: &'a str| {
+        let (rest, output) = f(input)?;
+        let span = Span {
+            start: input.len(),
+            end: rest.len(),
+        };
+        Ok((rest, (output, span)))
+    }
+}
 
 fn line_comment<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, &'a str, E> {
-    preceded(tag("//"), is_not("\n\r"))(input)
+    preceded(tag("//"), take_till(|c| c == '\n' || c == '\r'))(input)
 }
 
 fn sp<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
@@ -27,88 +46,188 @@ fn sp<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     )(input)
 }
 
+#[derive(Clone)]
+enum Token<'a> {
+    Space,
+    Newline,
+    Comment(&'a str),
+}
+
+/// Skips whitespace and comments like `sp`, returning the lines of the comments immediately
+/// preceding the next item. Comments at the end of a line, or followed by a blank line, are
+/// not included.
+fn docs<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, Vec<String>, E> {
+    let token = alt((
+        value(Token::Newline, char('\n')),
+        value(Token::Space, one_of(" \t\r")),
+        map(line_comment, Token::Comment),
+    ));
+    // Counts the newlines since the last comment, to tell apart trailing comments and blank
+    // lines.
+    let (rest, (mut docs, newlines)) = fold_many0(
+        token,
+        (Vec::new(), 0),
+        |(mut docs, newlines): (Vec<String>, usize), token| match token {
+            Token::Space => (docs, newlines),
+            Token::Newline => (docs, newlines + 1),
+            Token::Comment(text) => {
+                if newlines > 1 {
+                    docs.clear();
+                }
+                if newlines > 0 {
+                    let text = text.strip_prefix(' ').unwrap_or(text);
+                    docs.push(text.trim_end().to_string());
+                }
+                (docs, 0)
+            }
+        },
+    )(input)?;
+    if newlines > 1 {
+        docs.clear();
+    }
+    Ok((rest, docs))
+}
+
 fn ident<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, &'a str, E> {
-    context(
-        "ident",
-        recognize(pair(
-            alt((alpha1, tag("_"))),
-            many0(alt((alphanumeric1, tag("_")))),
+    recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0(alt((alphanumeric1, tag("_")))),
+    ))(input)
+}
+
+/// Matches the keyword `kw`, which must not be followed by more identifier characters.
+fn keyword<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    kw: &'static str,
+) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str, E> {
+    terminated(tag(kw), not(alt((alphanumeric1, tag("_")))))
+}
+
+fn package<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+    input: &'a str,
+) -> IResult<&'a str, (String, Span), E> {
+    preceded(
+        context("`package`", keyword("package")),
+        cut(preceded(
+            sp,
+            context(
+                "a package name",
+                spanned(map(ident, |ident| ident.to_snake())),
+            ),
         )),
     )(input)
 }
 
-fn package<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
+/// A reference to a struct, qualified as `package.name` if it is defined by another package.
+fn struct_ref<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, String, E> {
-    context(
-        "package",
-        preceded(
-            pair(tag("package"), sp),
-            terminated(map(ident, |ident| ident.to_snake()), sp),
+    map(
+        pair(
+            ident,
+            opt(preceded(char('.'), cut(context("a struct name", ident)))),
         ),
+        |(first, second)| match second {
+            Some(name) => format!("{}.{}", first.to_snake(), name.to_camel()),
+            None => first.to_camel(),
+        },
     )(input)
 }
 
 fn array_type<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, FieldType, E> {
-    let f32_ = map(tag("float32"), |_| FieldType::ArrayF32);
-    let f64_ = map(tag("float64"), |_| FieldType::ArrayF64);
-    let text_ = map(tag("text"), |_| FieldType::ArrayText);
-    let binary_ = map(tag("binary"), |_| FieldType::ArrayBinary);
-    let s = map(ident, |name| FieldType::ArrayStruct(name.to_camel()));
+    let u16_ = map(keyword("uint16"), |_| FieldType::ArrayU16);
+    let u32_ = map(keyword("uint32"), |_| FieldType::ArrayU32);
+    let u64_ = map(keyword("uint64"), |_| FieldType::ArrayU64);
+    let i8_ = map(keyword("int8"), |_| FieldType::ArrayI8);
+    let i16_ = map(keyword("int16"), |_| FieldType::ArrayI16);
+    let i32_ = map(keyword("int32"), |_| FieldType::ArrayI32);
+    let i64_ = map(keyword("int64"), |_| FieldType::ArrayI64);
+    let f32_ = map(keyword("float32"), |_| FieldType::ArrayF32);
+    let f64_ = map(keyword("float64"), |_| FieldType::ArrayF64);
+    let text_ = map(keyword("text"), |_| FieldType::ArrayText);
+    let binary_ = map(keyword("binary"), |_| FieldType::ArrayBinary);
+    let s = map(struct_ref, FieldType::ArrayStruct);
 
-    context("array-type", alt((f32_, f64_, text_, binary_, s)))(input)
+    context(
+        "a list element type",
+        alt((
+            u16_, u32_, u64_, i8_, i16_, i32_, i64_, f32_, f64_, text_, binary_, s,
+        )),
+    )(input)
 }
 
 fn field_type<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, FieldType, E> {
-    let bool_ = map(tag("bool"), |_| FieldType::Bool);
-    let u8_ = map(tag("uint8"), |_| FieldType::U8);
-    let u16_ = map(tag("uint16"), |_| FieldType::U16);
-    let u32_ = map(tag("uint32"), |_| FieldType::U32);
-    let u64_ = map(tag("uint64"), |_| FieldType::U64);
-    let i32_ = map(tag("int32"), |_| FieldType::I32);
-    let i64_ = map(tag("int64"), |_| FieldType::I64);
-    let f32_ = map(tag("float32"), |_| FieldType::F32);
-    let f64_ = map(tag("float64"), |_| FieldType::F64);
-    let timestamp_ = map(tag("timestamp"), |_| FieldType::Timestamp);
-    let text_ = map(tag("text"), |_| FieldType::Text);
-    let binary_ = map(tag("binary"), |_| FieldType::Binary);
-
-    let array_ = preceded(tuple((tag("["), sp, tag("]"), sp)), array_type);
-    let s = map(ident, |name| FieldType::Struct(name.to_camel()));
+    let bool_ = map(keyword("bool"), |_| FieldType::Bool);
+    let u8_ = map(keyword("uint8"), |_| FieldType::U8);
+    let u16_ = map(keyword("uint16"), |_| FieldType::U16);
+    let u32_ = map(keyword("uint32"), |_| FieldType::U32);
+    let u64_ = map(keyword("uint64"), |_| FieldType::U64);
+    let i8_ = map(keyword("int8"), |_| FieldType::I8);
+    let i16_ = map(keyword("int16"), |_| FieldType::I16);
+    let i32_ = map(keyword("int32"), |_| FieldType::I32);
+    let i64_ = map(keyword("int64"), |_| FieldType::I64);
+    let f32_ = map(keyword("float32"), |_| FieldType::F32);
+    let f64_ = map(keyword("float64"), |_| FieldType::F64);
+    let timestamp_ = map(keyword("timestamp"), |_| FieldType::Timestamp);
+    let text_ = map(keyword("text"), |_| FieldType::Text);
+    let binary_ = map(keyword("binary"), |_| FieldType::Binary);
+    let opaque8_ = map(keyword("opaque8"), |_| FieldType::Opaque8);
+    let opaque16_ = map(keyword("opaque16"), |_| FieldType::Opaque16);
+    let opaque32_ = map(keyword("opaque32"), |_| FieldType::Opaque32);
+    let opaque64_ = map(keyword("opaque64"), |_| FieldType::Opaque64);
+
+    let array_ = preceded(
+        tuple((tag("["), sp, context("`]`", tag("]")), sp)),
+        cut(array_type),
+    );
+    let s = map(struct_ref, FieldType::Struct);
 
     context(
-        "field-type",
+        "a field type",
         alt((
-            bool_, u8_, u16_, u32_, u64_, i32_, i64_, f32_, f64_, timestamp_, text_, binary_,
-            array_, s,
+            bool_, u8_, u16_, u32_, u64_, i8_, i16_, i32_, i64_, f32_, f64_, timestamp_, text_,
+            binary_, opaque8_, opaque16_, opaque32_, opaque64_, array_, s,
         )),
     )(input)
 }
 
 fn type_struct<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
-) -> IResult<&'a str, String, E> {
-    terminated(
-        preceded(pair(tag("type"), sp), map(ident, |ident| ident.to_camel())),
-        pair(sp, tag("struct")),
+) -> IResult<&'a str, (String, Span), E> {
+    preceded(
+        keyword("type"),
+        cut(delimited(
+            sp,
+            context(
+                "a struct name",
+                spanned(map(ident, |ident| ident.to_camel())),
+            ),
+            pair(sp, context("`struct`", keyword("struct"))),
+        )),
     )(input)
 }
 
 fn field_def<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, Field, E> {
-    delimited(
-        sp,
-        map(tuple((ident, sp, field_type)), |(name, _, ty)| Field {
-            name: {
-                let mut name = name.to_snake();
-                match name.as_str() {
+    map(
+        tuple((
+            docs,
+            context("a field name", spanned(ident)),
+            cut(preceded(sp, spanned(field_type))),
+        )),
+        |(docs, (schema_name, span), (ty, ty_span))| {
+            Field {
+                name: {
+                    let mut name = schema_name.to_snake();
+                    match name.as_str() {
                     // 2015 strict keywords.
                     | "as" | "break" | "const" | "continue" | "else" | "enum" | "false"
                     | "fn" | "for" | "if" | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut"
@@ -125,41 +244,117 @@ fn field_def<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
                     "self" | "super" | "extern" | "crate" => name += "_",
                     _ => (),
                 }
-                name
-            },
-            ty,
-        }),
-        sp,
+                    name
+                },
+                schema_name: schema_name.to_string(),
+                ty,
+                span,
+                ty_span,
+                docs,
+            }
+        },
     )(input)
 }
 
 fn struct_def<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, Struct, E> {
-    let fields = many1(delimited(sp, field_def, sp));
-    let body = delimited(tag("{"), fields, tag("}"));
+    let body = delimited(
+        context("`{`", tag("{")),
+        many1(field_def),
+        preceded(sp, context("a field or `}`", tag("}"))),
+    );
 
-    map(tuple((type_struct, sp, body)), |(name, _, fields)| Struct {
-        name,
-        fields,
-    })(input)
+    map(
+        tuple((docs, type_struct, cut(preceded(sp, body)))),
+        |(docs, (name, span), fields)| Struct {
+            name,
+            fields,
+            span,
+            docs,
+        },
+    )(input)
 }
 
 fn colfer<'a, E: ParseError<&'a str> + ContextError<&'a str>>(
     input: &'a str,
 ) -> IResult<&'a str, Colfer, E> {
-    let package = delimited(sp, package, sp);
-    let structs = many1(delimited(sp, struct_def, sp));
+    let package = preceded(sp, package);
+    let structs = many0(struct_def);
     context(
         "colfer",
-        map(tuple((package, structs, eof)), |(package, structs, _)| {
-            Colfer { package, structs }
-        }),
+        map(
+            tuple((package, structs, preceded(sp, context("`type`", eof)))),
+            |((package, package_span), structs, _)| Colfer {
+                package,
+                structs,
+                package_span,
+            },
+        ),
     )(input)
 }
 
-pub fn parse(input: &str) -> Result<Colfer, nom::Err<VerboseError<&str>>> {
-    colfer::<VerboseError<&str>>(input).map(|(_, colfer)| colfer)
+/// Converts a span recorded by `spanned` to offsets into an input of `len` bytes.
+fn resolve(span: &mut Span, len: usize) {
+    *span = Span {
+        start: len - span.start,
+        end: len - span.end,
+    };
+}
+
+/// Parses a field type as written in schemas, such as `[]text`.
+pub(crate) fn parse_type(input: &str) -> Option<FieldType> {
+    all_consuming(delimited(sp, field_type::<VerboseError<&str>>, sp))(input)
+        .ok()
+        .map(|(_, ty)| ty)
+}
+
+pub fn parse(input: &str) -> Result<Colfer, Diagnostic> {
+    let err = match colfer::<VerboseError<&str>>(input) {
+        Ok((_, mut colfer)) => {
+            resolve(&mut colfer.package_span, input.len());
+            for s in &mut colfer.structs {
+                resolve(&mut s.span, input.len());
+                for f in &mut s.fields {
+                    resolve(&mut f.span, input.len());
+                    resolve(&mut f.ty_span, input.len());
+                }
+            }
+            return Ok(colfer);
+        }
+        Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => err,
+        Err(nom::Err::Incomplete(_)) => VerboseError { errors: Vec::new() },
+    };
+
+    // The innermost context names what was expected where parsing failed.
+    let (rest, expected) = err
+        .errors
+        .iter()
+        .find_map(|(rest, kind)| match kind {
+            VerboseErrorKind::Context(expected) => Some((*rest, *expected)),
+            _ => None,
+        })
+        .unwrap_or(("", "end of file"));
+    let start = input.len() - rest.len();
+
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let found = match rest.chars().next() {
+        Some(c) if is_ident(c) => rest.find(|c| !is_ident(c)).map_or(rest, |end| &rest[..end]),
+        Some(c) => &rest[..c.len_utf8()],
+        None => "",
+    };
+    let message = if found.is_empty() {
+        format!("expected {}, found end of file", expected)
+    } else {
+        format!("expected {}, found `{}`", expected, found.escape_debug())
+    };
+    Err(Diagnostic::new(
+        Span {
+            start,
+            end: start + found.len(),
+        },
+        message,
+    ))
 }
 
 #[cfg(test)]
@@ -167,14 +362,24 @@ mod tests {
     use super::*;
     use nom::error::VerboseError;
 
+    /// Clears the spans of `field`, which hold remaining lengths until resolved by `parse`.
+    fn unspanned(field: Field) -> Field {
+        Field {
+            span: Span::default(),
+            ty_span: Span::default(),
+            ..field
+        }
+    }
+
     #[test]
     fn test_package() {
         assert_eq!(
-            package::<VerboseError<&str>>("package MyPkg"),
+            package::<VerboseError<&str>>("package MyPkg").map(|(rest, (name, _))| (rest, name)),
             Ok(("", "my_pkg".to_string()))
         );
         assert_eq!(
-            package::<VerboseError<&str>>("package     MyPkg"),
+            package::<VerboseError<&str>>("package     MyPkg")
+                .map(|(rest, (name, _))| (rest, name)),
             Ok(("", "my_pkg".to_string()))
         );
     }
@@ -229,11 +434,36 @@ mod tests {
             field_type::<VerboseError<&str>>("binary"),
             Ok(("", FieldType::Binary))
         );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("int8"),
+            Ok(("", FieldType::I8))
+        );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("int16"),
+            Ok(("", FieldType::I16))
+        );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("opaque64"),
+            Ok(("", FieldType::Opaque64))
+        );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("opaque"),
+            Ok(("", FieldType::Struct("Opaque".to_string())))
+        );
         assert_eq!(
             field_type::<VerboseError<&str>>("abc"),
             Ok(("", FieldType::Struct("Abc".to_string())))
         );
 
+        assert_eq!(
+            field_type::<VerboseError<&str>>("other_pkg.abc"),
+            Ok(("", FieldType::Struct("other_pkg.Abc".to_string())))
+        );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("[]OtherPkg.abc"),
+            Ok(("", FieldType::ArrayStruct("other_pkg.Abc".to_string())))
+        );
+
         assert_eq!(
             field_type::<VerboseError<&str>>("[]float32"),
             Ok(("", FieldType::ArrayF32))
@@ -246,16 +476,26 @@ mod tests {
             field_type::<VerboseError<&str>>("[] float32"),
             Ok(("", FieldType::ArrayF32))
         );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("[]int8"),
+            Ok(("", FieldType::ArrayI8))
+        );
+        assert_eq!(
+            field_type::<VerboseError<&str>>("[]uint64"),
+            Ok(("", FieldType::ArrayU64))
+        );
     }
 
     #[test]
     fn test_type_struct() {
         assert_eq!(
-            type_struct::<VerboseError<&str>>("type Abc struct"),
+            type_struct::<VerboseError<&str>>("type Abc struct")
+                .map(|(rest, (name, _))| (rest, name)),
             Ok(("", "Abc".to_string()))
         );
         assert_eq!(
-            type_struct::<VerboseError<&str>>("type    Abc    struct"),
+            type_struct::<VerboseError<&str>>("type    Abc    struct")
+                .map(|(rest, (name, _))| (rest, name)),
             Ok(("", "Abc".to_string()))
         );
     }
@@ -263,23 +503,66 @@ mod tests {
     #[test]
     fn test_field_def() {
         assert_eq!(
-            field_def::<VerboseError<&str>>("abc int32"),
+            field_def::<VerboseError<&str>>("abc int32").map(|(rest, f)| (rest, unspanned(f))),
             Ok((
                 "",
                 Field {
                     name: "abc".to_string(),
-                    ty: FieldType::I32
+                    schema_name: "abc".to_string(),
+                    ty: FieldType::I32,
+                    span: Span::default(),
+                    ty_span: Span::default(),
+                    docs: vec![]
                 }
             ))
         );
 
         assert_eq!(
-            field_def::<VerboseError<&str>>("abc       int32"),
+            field_def::<VerboseError<&str>>("abc       int32")
+                .map(|(rest, f)| (rest, unspanned(f))),
             Ok((
                 "",
                 Field {
                     name: "abc".to_string(),
-                    ty: FieldType::I32
+                    schema_name: "abc".to_string(),
+                    ty: FieldType::I32,
+                    span: Span::default(),
+                    ty_span: Span::default(),
+                    docs: vec![]
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_field_def_rename() {
+        assert_eq!(
+            field_def::<VerboseError<&str>>("PascalCase text")
+                .map(|(rest, f)| (rest, unspanned(f))),
+            Ok((
+                "",
+                Field {
+                    name: "pascal_case".to_string(),
+                    schema_name: "PascalCase".to_string(),
+                    ty: FieldType::Text,
+                    span: Span::default(),
+                    ty_span: Span::default(),
+                    docs: vec![]
+                }
+            ))
+        );
+
+        assert_eq!(
+            field_def::<VerboseError<&str>>("as binary").map(|(rest, f)| (rest, unspanned(f))),
+            Ok((
+                "",
+                Field {
+                    name: "r#as".to_string(),
+                    schema_name: "as".to_string(),
+                    ty: FieldType::Binary,
+                    span: Span::default(),
+                    ty_span: Span::default(),
+                    docs: vec![]
                 }
             ))
         );
@@ -293,7 +576,15 @@ mod tests {
                 value1 int32
                 value2 bool
             }"#
-            ),
+            )
+            .map(|(rest, s)| (
+                rest,
+                Struct {
+                    fields: s.fields.into_iter().map(unspanned).collect(),
+                    span: Span::default(),
+                    ..s
+                }
+            )),
             Ok((
                 "",
                 Struct {
@@ -301,18 +592,111 @@ mod tests {
                     fields: vec![
                         Field {
                             name: "value1".to_string(),
-                            ty: FieldType::I32
+                            schema_name: "value1".to_string(),
+                            ty: FieldType::I32,
+                            span: Span::default(),
+                            ty_span: Span::default(),
+                            docs: vec![]
                         },
                         Field {
                             name: "value2".to_string(),
-                            ty: FieldType::Bool
+                            schema_name: "value2".to_string(),
+                            ty: FieldType::Bool,
+                            span: Span::default(),
+                            ty_span: Span::default(),
+                            docs: vec![]
                         }
-                    ]
+                    ],
+                    span: Span::default(),
+                    docs: vec![]
                 }
             ))
         )
     }
 
+    #[test]
+    fn test_spans() {
+        let colfer = parse("package demo\n\ntype abc struct {\n\tvalue []xyz\n}\n").unwrap();
+        assert_eq!(colfer.package_span, Span { start: 8, end: 12 });
+        let s = &colfer.structs[0];
+        assert_eq!(s.span, Span { start: 19, end: 22 });
+        assert_eq!(s.fields[0].span, Span { start: 33, end: 38 });
+        assert_eq!(s.fields[0].ty_span, Span { start: 39, end: 44 });
+    }
+
+    #[test]
+    fn test_docs() {
+        let colfer = parse(
+            r#"// Package demo.
+package demo
+
+// Ignored, as it is followed by a blank line.
+
+// A is documented.
+//
+// With two paragraphs.
+type a struct {
+	// B is documented.
+	b int32 // Trailing comments are ignored.
+	c int32
+	//D is documented.
+	d int32
+}
+"#,
+        )
+        .unwrap();
+        let s = &colfer.structs[0];
+        assert_eq!(s.docs, vec!["A is documented.", "", "With two paragraphs."]);
+        let docs: Vec<_> = s.fields.iter().map(|f| f.docs.clone()).collect();
+        assert_eq!(
+            docs,
+            vec![
+                vec!["B is documented.".to_string()],
+                vec![],
+                vec!["D is documented.".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let cases = [
+            ("", "expected `package`, found end of file", 0),
+            ("package 1x", "expected a package name, found `1x`", 8),
+            ("package demo\ntype a {", "expected `struct`, found `{`", 20),
+            (
+                "package demo\ntype a struct {\n\tb\n}",
+                "expected a field type, found `}`",
+                32,
+            ),
+            (
+                "package demo\ntype a struct {\n\tb []1\n}",
+                "expected a list element type, found `1`",
+                34,
+            ),
+            (
+                "package demo\ntype a struct {\n\tb int32 !\n}",
+                "expected a field or `}`, found `!`",
+                38,
+            ),
+            (
+                "package demo\ntype a struct {\n\tb int32\n",
+                "expected a field or `}`, found end of file",
+                38,
+            ),
+            (
+                "package demo\ntype a struct {\n\tb int32\n}\n}",
+                "expected `type`, found `}`",
+                40,
+            ),
+        ];
+        for (source, message, start) in cases.iter() {
+            let diagnostic = parse(source).unwrap_err();
+            assert_eq!(&diagnostic.message, message, "{:?}", source);
+            assert_eq!(diagnostic.span.start, *start, "{:?}", source);
+        }
+    }
+
     #[test]
     fn test_comment() {
         assert_eq!(line_comment::<VerboseError<&str>>("//abc"), Ok(("", "abc")));
@@ -0,0 +1,185 @@
+//! The `colfer-gen` command-line tool, which generates Rust or TypeScript code from `.colf`
+//! schemas like `colfer_build::Config` does in build scripts.
+//!
+//! Every option of `Config` has a flag, except for the hooks of `Config::code_generator` and
+//! custom generators other than `--lang`, which are Rust code and thus only available in
+//! build scripts.
+
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Result;
+use colfer_build::{Config, SchemaError, TypeScriptGenerator};
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "colfer-gen",
+    about = "Generates Rust or TypeScript code from colfer schemas."
+)]
+struct Args {
+    /// The `.colf` schema files.
+    #[structopt(parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
+
+    /// The directory of the generated files.
+    #[structopt(short, long, parse(from_os_str))]
+    out_dir: PathBuf,
+
+    /// The language of the generated code, `rust` or `typescript`.
+    #[structopt(short, long, default_value = "rust")]
+    lang: Lang,
+
+    /// Generate borrowed `<Name>View<'a>` types.
+    #[structopt(long)]
+    views: bool,
+
+    /// Generate strict decoders.
+    #[structopt(long)]
+    strict: bool,
+
+    /// Generate decoders which skip unknown fields.
+    #[structopt(long)]
+    skip_unknown_fields: bool,
+
+    /// Generate decoders which retain unknown fields.
+    #[structopt(long)]
+    retain_unknown_fields: bool,
+
+    /// Derive `Serialize` and `Deserialize` for the generated structs.
+    #[structopt(long)]
+    serde: bool,
+
+    /// Derive `Eq` for the generated structs without floating point fields.
+    #[structopt(long)]
+    derive_eq: bool,
+
+    /// Derive `Hash` for the generated structs without floating point fields.
+    #[structopt(long)]
+    derive_hash: bool,
+
+    /// Derive `PartialOrd` for the generated structs.
+    #[structopt(long)]
+    derive_partial_ord: bool,
+
+    /// The visibility of generated fields, such as `pub(crate)`.
+    #[structopt(long, default_value = "pub")]
+    field_visibility: String,
+
+    /// Map the fields of a schema type to a Rust type, as `binary=bytes::Bytes`. Repeatable,
+    /// the last mapping of a type wins.
+    #[structopt(long, parse(try_from_str = parse_pair), number_of_values = 1)]
+    map_type: Vec<(String, String)>,
+
+    /// Map a field to a Rust type, as `package.struct.field=Type`, which takes precedence
+    /// over `--map-type`. Repeatable, the last mapping of a field wins.
+    #[structopt(long, parse(try_from_str = parse_pair), number_of_values = 1)]
+    map_field: Vec<(String, String)>,
+
+    /// Add an attribute to the structs of `.`, a `package` or a `package.struct`, as
+    /// `shop=#[non_exhaustive]`. Repeatable.
+    #[structopt(long, parse(try_from_str = parse_pair), number_of_values = 1)]
+    type_attribute: Vec<(String, String)>,
+
+    /// Add an attribute to the fields of `.`, a `package`, a `package.struct` or a
+    /// `package.struct.field`, as `shop.item.quantity=#[serde(rename = "qty")]`. Repeatable.
+    #[structopt(long, parse(try_from_str = parse_pair), number_of_values = 1)]
+    field_attribute: Vec<(String, String)>,
+
+    /// Also generate `_colfer_includes.rs`, which declares the module of every package.
+    #[structopt(long)]
+    include_file: bool,
+
+    /// Fail if the generated files are missing or differ, instead of writing them.
+    #[structopt(long)]
+    check: bool,
+}
+
+enum Lang {
+    Rust,
+    TypeScript,
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "rust" => Ok(Lang::Rust),
+            "typescript" | "ts" => Ok(Lang::TypeScript),
+            _ => Err(format!(
+                "unknown language `{}`, expected `rust` or `typescript`",
+                s
+            )),
+        }
+    }
+}
+
+/// Parses `key=value`, splitting at the first `=` as values such as attributes may contain
+/// more.
+fn parse_pair(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+            Ok((key.to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected `key=value`, found `{}`", s)),
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut config = Config::default()
+        .out_dir(args.out_dir)
+        .views(args.views)
+        .strict(args.strict)
+        .skip_unknown_fields(args.skip_unknown_fields)
+        .retain_unknown_fields(args.retain_unknown_fields)
+        .serde(args.serde)
+        .derive_eq(args.derive_eq)
+        .derive_hash(args.derive_hash)
+        .derive_partial_ord(args.derive_partial_ord)
+        .field_visibility(args.field_visibility)
+        .include_file(args.include_file);
+    for (schema_type, rust_type) in args.map_type {
+        config = config.map_type(schema_type, rust_type);
+    }
+    for (path, rust_type) in args.map_field {
+        config = config.map_field(path, rust_type);
+    }
+    for (path, attribute) in args.type_attribute {
+        config = config.type_attribute(path, attribute);
+    }
+    for (path, attribute) in args.field_attribute {
+        config = config.field_attribute(path, attribute);
+    }
+    if let Lang::TypeScript = args.lang {
+        config = config.generator(TypeScriptGenerator);
+    }
+
+    if !args.check {
+        return config.compile(&args.files);
+    }
+
+    let mut stale = 0;
+    for (path, code) in config.generate(&args.files)? {
+        if fs::read_to_string(&path).ok().as_ref() != Some(&code) {
+            eprintln!("`{}` is not up to date", path.display());
+            stale += 1;
+        }
+    }
+    if stale > 0 {
+        anyhow::bail!("{} generated file(s) are not up to date", stale);
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Args::from_args()) {
+        // Schema errors are already formatted like compiler errors.
+        match err.downcast_ref::<SchemaError>() {
+            Some(err) => eprintln!("{}", err),
+            None => eprintln!("error: {:#}", err),
+        }
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Conversion between dynamic messages and JSON.
+//!
+//! Messages are objects keyed by the field names of the schema, timestamps are objects with
+//! `seconds` and `nano_seconds`, and binaries and opaque values are arrays of bytes, which
+//! matches the serde representation of the generated structs.
+
+use std::convert::TryFrom;
+
+use anyhow::{anyhow, Context, Result};
+use colfer::DateTime;
+use colfer_build::ast::{Colfer, FieldType, Schema, Struct};
+use colfer_build::dynamic::{DynamicMessage, Value};
+use serde_json::{json, Value as Json};
+
+/// Converts `message` to JSON, omitting absent fields.
+pub fn to_json(message: &DynamicMessage) -> Json {
+    Json::Object(
+        message
+            .fields()
+            .map(|(name, value)| (name.to_string(), value_to_json(value)))
+            .collect(),
+    )
+}
+
+fn value_to_json(value: &Value) -> Json {
+    match value {
+        Value::Bool(v) => Json::from(*v),
+        Value::U8(v) => Json::from(*v),
+        Value::U16(v) => Json::from(*v),
+        Value::U32(v) => Json::from(*v),
+        Value::U64(v) => Json::from(*v),
+        Value::I8(v) => Json::from(*v),
+        Value::I16(v) => Json::from(*v),
+        Value::I32(v) => Json::from(*v),
+        Value::I64(v) => Json::from(*v),
+        Value::F32(v) => Json::from(*v),
+        Value::F64(v) => Json::from(*v),
+        Value::Timestamp(v) => json!({ "seconds": v.seconds, "nano_seconds": v.nano_seconds }),
+        Value::Text(v) => Json::from(v.as_str()),
+        Value::Binary(v) => Json::from(v.as_slice()),
+        Value::Opaque8(v) => Json::from(&v[..]),
+        Value::Opaque16(v) => Json::from(&v[..]),
+        Value::Opaque32(v) => Json::from(&v[..]),
+        Value::Opaque64(v) => Json::from(&v[..]),
+        Value::Struct(v) => to_json(v),
+        Value::ArrayU16(v) => Json::from(v.as_slice()),
+        Value::ArrayU32(v) => Json::from(v.as_slice()),
+        Value::ArrayU64(v) => Json::from(v.as_slice()),
+        Value::ArrayI8(v) => Json::from(v.as_slice()),
+        Value::ArrayI16(v) => Json::from(v.as_slice()),
+        Value::ArrayI32(v) => Json::from(v.as_slice()),
+        Value::ArrayI64(v) => Json::from(v.as_slice()),
+        Value::ArrayF32(v) => Json::from(v.as_slice()),
+        Value::ArrayF64(v) => Json::from(v.as_slice()),
+        Value::ArrayText(v) => Json::from(v.as_slice()),
+        Value::ArrayBinary(v) => Json::Array(v.iter().map(|v| Json::from(v.as_slice())).collect()),
+        Value::ArrayStruct(v) => Json::Array(v.iter().map(to_json).collect()),
+    }
+}
+
+/// Converts `json` to a message of struct `name` of `schema`. Fields that are missing or
+/// `null` are absent.
+pub fn from_json(schema: &Schema, name: &str, json: &Json) -> Result<DynamicMessage> {
+    let (colfer, s) = schema
+        .find_struct(name)
+        .ok_or_else(|| anyhow!("struct `{}` is not defined", name))?;
+    message_from_json(schema, colfer, s, json)
+}
+
+fn message_from_json(
+    schema: &Schema,
+    colfer: &Colfer,
+    s: &Struct,
+    json: &Json,
+) -> Result<DynamicMessage> {
+    let object = json
+        .as_object()
+        .ok_or_else(|| anyhow!("expected an object, found `{}`", json))?;
+
+    let mut message = DynamicMessage::new(format!("{}.{}", colfer.package, s.name));
+    for (key, json) in object {
+        let field = s
+            .fields
+            .iter()
+            .find(|f| &f.schema_name == key)
+            .ok_or_else(|| anyhow!("struct `{}` has no field `{}`", s.name, key))?;
+        if json.is_null() {
+            continue;
+        }
+        let value = value_from_json(schema, colfer, &field.ty, json)
+            .with_context(|| format!("invalid value for field `{}.{}`", s.name, key))?;
+        message.set(key.clone(), value);
+    }
+    Ok(message)
+}
+
+fn value_from_json(schema: &Schema, colfer: &Colfer, ty: &FieldType, json: &Json) -> Result<Value> {
+    let resolve = |name: &str| {
+        schema
+            .resolve(&colfer.package, name)
+            .ok_or_else(|| anyhow!("struct `{}` is not defined", name))
+    };
+    Ok(match ty {
+        FieldType::Bool => Value::Bool(
+            json.as_bool()
+                .ok_or_else(|| anyhow!("expected a boolean, found `{}`", json))?,
+        ),
+        FieldType::U8 => Value::U8(int(json)?),
+        FieldType::U16 => Value::U16(int(json)?),
+        FieldType::U32 => Value::U32(int(json)?),
+        FieldType::U64 => Value::U64(int(json)?),
+        FieldType::I8 => Value::I8(int(json)?),
+        FieldType::I16 => Value::I16(int(json)?),
+        FieldType::I32 => Value::I32(int(json)?),
+        FieldType::I64 => Value::I64(int(json)?),
+        FieldType::F32 => Value::F32(float(json)? as f32),
+        FieldType::F64 => Value::F64(float(json)?),
+        FieldType::Timestamp => Value::Timestamp(timestamp(json)?),
+        FieldType::Text => Value::Text(text(json)?),
+        FieldType::Binary => Value::Binary(array(json, int)?),
+        FieldType::Opaque8 => Value::Opaque8(opaque(json)?),
+        FieldType::Opaque16 => Value::Opaque16(opaque(json)?),
+        FieldType::Opaque32 => Value::Opaque32(opaque(json)?),
+        FieldType::Opaque64 => Value::Opaque64(opaque(json)?),
+        FieldType::Struct(name) => {
+            let (colfer, s) = resolve(name)?;
+            Value::Struct(message_from_json(schema, colfer, s, json)?)
+        }
+        FieldType::ArrayU16 => Value::ArrayU16(array(json, int)?),
+        FieldType::ArrayU32 => Value::ArrayU32(array(json, int)?),
+        FieldType::ArrayU64 => Value::ArrayU64(array(json, int)?),
+        FieldType::ArrayI8 => Value::ArrayI8(array(json, int)?),
+        FieldType::ArrayI16 => Value::ArrayI16(array(json, int)?),
+        FieldType::ArrayI32 => Value::ArrayI32(array(json, int)?),
+        FieldType::ArrayI64 => Value::ArrayI64(array(json, int)?),
+        FieldType::ArrayF32 => Value::ArrayF32(array(json, |v| Ok(float(v)? as f32))?),
+        FieldType::ArrayF64 => Value::ArrayF64(array(json, float)?),
+        FieldType::ArrayText => Value::ArrayText(array(json, text)?),
+        FieldType::ArrayBinary => Value::ArrayBinary(array(json, |v| array(v, int))?),
+        FieldType::ArrayStruct(name) => {
+            let (colfer, s) = resolve(name)?;
+            Value::ArrayStruct(array(json, |v| message_from_json(schema, colfer, s, v))?)
+        }
+    })
+}
+
+fn int<T: TryFrom<i128>>(json: &Json) -> Result<T> {
+    let n = json
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| json.as_u64().map(i128::from))
+        .ok_or_else(|| anyhow!("expected an integer, found `{}`", json))?;
+    T::try_from(n).map_err(|_| anyhow!("integer {} is out of range", n))
+}
+
+fn float(json: &Json) -> Result<f64> {
+    json.as_f64()
+        .ok_or_else(|| anyhow!("expected a number, found `{}`", json))
+}
+
+fn text(json: &Json) -> Result<String> {
+    json.as_str()
+        .map(ToString::to_string)
+        .ok_or_else(|| anyhow!("expected a string, found `{}`", json))
+}
+
+fn timestamp(json: &Json) -> Result<DateTime> {
+    let object = json
+        .as_object()
+        .ok_or_else(|| anyhow!("expected a timestamp object, found `{}`", json))?;
+    if let Some(key) = object
+        .keys()
+        .find(|key| *key != "seconds" && *key != "nano_seconds")
+    {
+        return Err(anyhow!("timestamps have no field `{}`", key));
+    }
+    let field = |name| match &json[name] {
+        Json::Null => Ok(0),
+        value => int::<i64>(value).with_context(|| format!("invalid `{}`", name)),
+    };
+    let nano_seconds = field("nano_seconds")?;
+    if !(0..1_000_000_000).contains(&nano_seconds) {
+        return Err(anyhow!("`nano_seconds` is out of range"));
+    }
+    Ok(DateTime {
+        seconds: field("seconds")?,
+        nano_seconds: nano_seconds as u32,
+    })
+}
+
+fn opaque<const N: usize>(json: &Json) -> Result<[u8; N]> {
+    let data = array(json, int)?;
+    let len = data.len();
+    <[u8; N]>::try_from(data).map_err(|_| anyhow!("expected {} bytes, found {}", N, len))
+}
+
+fn array<T>(json: &Json, f: impl Fn(&Json) -> Result<T>) -> Result<Vec<T>> {
+    json.as_array()
+        .ok_or_else(|| anyhow!("expected an array, found `{}`", json))?
+        .iter()
+        .map(f)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+package demo
+
+type node struct {
+	id uint16
+	name text
+	created timestamp
+	data []binary
+	children []node
+	hash opaque16
+	deltas []int32
+}
+"#;
+
+    #[test]
+    fn test_roundtrip() {
+        let schema = Schema::new(vec![colfer_build::parse_schema(SCHEMA).unwrap()]);
+        let json = json!({
+            "id": 7,
+            "name": "root",
+            "created": { "seconds": 1_600_000_000, "nano_seconds": 5 },
+            "data": [[1, 2], []],
+            "children": [{ "name": "child" }, {}],
+            "hash": [0, 255],
+            "deltas": [-1, 2],
+        });
+        let message = from_json(&schema, "Node", &json).unwrap();
+        let data = message.to_vec(&schema).unwrap();
+        let decoded = DynamicMessage::from_bytes(&schema, "Node", &data).unwrap();
+        assert_eq!(to_json(&decoded), json);
+    }
+
+    #[test]
+    fn test_errors() {
+        let schema = Schema::new(vec![colfer_build::parse_schema(SCHEMA).unwrap()]);
+        for json in &[
+            json!([]),
+            json!({ "size": 1 }),
+            json!({ "id": 65536 }),
+            json!({ "id": -1 }),
+            json!({ "name": 1 }),
+            json!({ "created": { "nano_seconds": -1 } }),
+            json!({ "children": [{ "id": "1" }] }),
+            json!({ "hash": [1, 2, 3] }),
+            json!({ "deltas": [2_147_483_648i64] }),
+        ] {
+            assert!(from_json(&schema, "Node", json).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_timestamp_errors() {
+        let schema = Schema::new(vec![colfer_build::parse_schema(SCHEMA).unwrap()]);
+        let err = from_json(
+            &schema,
+            "Node",
+            &json!({ "created": { "seconds": -1, "nanos": 5 } }),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "invalid value for field `Node.created`: timestamps have no field `nanos`"
+        );
+        let err = from_json(
+            &schema,
+            "Node",
+            &json!({ "created": { "nano_seconds": 1_000_000_000 } }),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!("{:#}", err),
+            "invalid value for field `Node.created`: `nano_seconds` is out of range"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! The `colfer` command-line tool, which decodes and encodes messages with a `.colf` schema.
+
+mod json;
+mod tree;
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use case::CaseExt;
+use colfer_build::ast::Schema;
+use colfer_build::compat;
+use colfer_build::dynamic::DynamicMessage;
+use colfer_build::SchemaError;
+use structopt::StructOpt;
+
+#[derive(StructOpt)]
+#[structopt(
+    name = "colfer",
+    about = "Decodes and encodes colfer messages with a schema."
+)]
+enum Command {
+    /// Decodes a binary message and prints it as JSON or as a tree.
+    Decode {
+        #[structopt(flatten)]
+        target: Target,
+
+        /// The output format, `json` or `tree`.
+        #[structopt(short, long, default_value = "json")]
+        format: Format,
+
+        /// The binary input file, stdin if omitted.
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+    },
+
+    /// Encodes a message from JSON to binary.
+    Encode {
+        #[structopt(flatten)]
+        target: Target,
+
+        /// The binary output file, stdout if omitted.
+        #[structopt(short, long, parse(from_os_str))]
+        output: Option<PathBuf>,
+
+        /// The JSON input file, stdin if omitted.
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+    },
+
+    /// Checks that peers with a new version of a schema can exchange messages with peers
+    /// with the old one and that code generated from it keeps compiling, failing on breaking
+    /// changes such as renamed structs or fields.
+    Compat {
+        /// The old `.colf` schema file, or a directory of them.
+        #[structopt(parse(from_os_str))]
+        old: PathBuf,
+
+        /// The new `.colf` schema file, or a directory of them.
+        #[structopt(parse(from_os_str))]
+        new: PathBuf,
+    },
+}
+
+#[derive(StructOpt)]
+struct Target {
+    /// The `.colf` schema file, or a directory of them.
+    #[structopt(parse(from_os_str))]
+    schema: PathBuf,
+
+    /// The struct of the message, either as named in the schema or as generated, and
+    /// qualified as `package.name` if several packages define it.
+    #[structopt(name = "struct")]
+    name: String,
+}
+
+enum Format {
+    Json,
+    Tree,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "tree" => Ok(Format::Tree),
+            _ => Err(format!("unknown format `{}`, expected `json` or `tree`", s)),
+        }
+    }
+}
+
+impl Target {
+    /// Loads the schema, returning it along with the generated name of the struct.
+    fn load(&self) -> Result<(Schema, String)> {
+        let schema = load_schema(&self.schema)?;
+        let camel = match self.name.rsplit_once('.') {
+            Some((package, name)) => format!("{}.{}", package, name.to_camel()),
+            None => self.name.to_camel(),
+        };
+        let name = [self.name.clone(), camel]
+            .iter()
+            .find(|name| schema.find_struct(name).is_some())
+            .cloned()
+            .ok_or_else(|| {
+                anyhow!(
+                    "struct `{}` is not defined in the schema, or defined by several packages",
+                    self.name
+                )
+            })?;
+        Ok((schema, name))
+    }
+}
+
+/// Parses the schema file `path`, or the `.colf` files of directory `path` together.
+fn load_schema(path: &Path) -> Result<Schema> {
+    if !path.is_dir() {
+        return colfer_build::parse_schema_files(&[path]);
+    }
+    let mut files = Vec::new();
+    for entry in
+        fs::read_dir(path).with_context(|| format!("failed to read `{}`", path.display()))?
+    {
+        let file = entry?.path();
+        if file.extension() == Some("colf".as_ref()) {
+            files.push(file);
+        }
+    }
+    if files.is_empty() {
+        anyhow::bail!("no `.colf` files in `{}`", path.display());
+    }
+    files.sort();
+    colfer_build::parse_schema_files(&files)
+}
+
+fn read_input(input: Option<&Path>) -> Result<Vec<u8>> {
+    match input {
+        Some(path) => {
+            fs::read(path).with_context(|| format!("failed to read `{}`", path.display()))
+        }
+        None => {
+            let mut data = Vec::new();
+            io::stdin()
+                .read_to_end(&mut data)
+                .context("failed to read stdin")?;
+            Ok(data)
+        }
+    }
+}
+
+fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Decode {
+            target,
+            format,
+            input,
+        } => {
+            let (schema, name) = target.load()?;
+            let data = read_input(input.as_deref())?;
+            let message = DynamicMessage::from_bytes(&schema, &name, &data)
+                .with_context(|| format!("failed to decode `{}`", name))?;
+            let out = match format {
+                Format::Json => serde_json::to_string_pretty(&json::to_json(&message))? + "\n",
+                Format::Tree => tree::to_tree(&schema, &message),
+            };
+            io::stdout().write_all(out.as_bytes())?;
+        }
+        Command::Encode {
+            target,
+            output,
+            input,
+        } => {
+            let (schema, name) = target.load()?;
+            let data = read_input(input.as_deref())?;
+            let value = serde_json::from_slice(&data).context("failed to parse JSON")?;
+            let data = json::from_json(&schema, &name, &value)?
+                .to_vec(&schema)
+                .with_context(|| format!("failed to encode `{}`", name))?;
+            match output {
+                Some(path) => fs::write(&path, data)
+                    .with_context(|| format!("failed to write `{}`", path.display()))?,
+                None => io::stdout().write_all(&data)?,
+            }
+        }
+        Command::Compat { old, new } => {
+            let old = load_schema(&old)?;
+            let new = load_schema(&new)?;
+            let changes = compat::diff_schemas(&old, &new);
+            for change in &changes {
+                let kind = if change.is_breaking() {
+                    "breaking"
+                } else {
+                    "compatible"
+                };
+                println!("{}: {}", kind, change);
+            }
+            let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+            if breaking > 0 {
+                anyhow::bail!("{} breaking change(s)", breaking);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Command::from_args()) {
+        // Schema errors are already formatted like compiler errors.
+        match err.downcast_ref::<SchemaError>() {
+            Some(err) => eprintln!("{}", err),
+            None => eprintln!("error: {:#}", err),
+        }
+        std::process::exit(1);
+    }
+}
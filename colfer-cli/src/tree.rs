@@ -0,0 +1,157 @@
+//! Human-readable rendering of dynamic messages.
+
+use std::fmt::Write;
+
+use colfer_build::ast::Schema;
+use colfer_build::dynamic::{DynamicMessage, Value};
+
+/// Renders `message` as an indented tree, listing the fields that are present in schema order.
+pub fn to_tree(schema: &Schema, message: &DynamicMessage) -> String {
+    let mut out = String::new();
+    write_message(&mut out, schema, message, 0);
+    out.push('\n');
+    out
+}
+
+fn indent(out: &mut String, level: usize) {
+    for _ in 0..level {
+        out.push_str("  ");
+    }
+}
+
+fn write_message(out: &mut String, schema: &Schema, message: &DynamicMessage, level: usize) {
+    let fields: Vec<_> = match schema.find_struct(message.name()) {
+        Some((_, s)) => s
+            .fields
+            .iter()
+            .filter_map(|f| Some((f.schema_name.as_str(), message.get(&f.schema_name)?)))
+            .collect(),
+        None => message.fields().collect(),
+    };
+    if fields.is_empty() {
+        write!(out, "{} {{}}", message.name()).unwrap();
+        return;
+    }
+
+    writeln!(out, "{} {{", message.name()).unwrap();
+    for (name, value) in fields {
+        indent(out, level + 1);
+        write!(out, "{}: ", name).unwrap();
+        write_value(out, schema, value, level + 1);
+        out.push('\n');
+    }
+    indent(out, level);
+    out.push('}');
+}
+
+fn write_list<T>(
+    out: &mut String,
+    items: &[T],
+    level: usize,
+    mut f: impl FnMut(&mut String, &T, usize),
+) {
+    out.push_str("[\n");
+    for item in items {
+        indent(out, level + 1);
+        f(out, item, level + 1);
+        out.push('\n');
+    }
+    indent(out, level);
+    out.push(']');
+}
+
+fn write_binary(out: &mut String, data: &[u8]) {
+    out.push_str("0x");
+    for b in data {
+        write!(out, "{:02x}", b).unwrap();
+    }
+}
+
+fn write_value(out: &mut String, schema: &Schema, value: &Value, level: usize) {
+    match value {
+        Value::Bool(v) => write!(out, "{}", v),
+        Value::U8(v) => write!(out, "{}", v),
+        Value::U16(v) => write!(out, "{}", v),
+        Value::U32(v) => write!(out, "{}", v),
+        Value::U64(v) => write!(out, "{}", v),
+        Value::I8(v) => write!(out, "{}", v),
+        Value::I16(v) => write!(out, "{}", v),
+        Value::I32(v) => write!(out, "{}", v),
+        Value::I64(v) => write!(out, "{}", v),
+        Value::F32(v) => write!(out, "{:?}", v),
+        Value::F64(v) => write!(out, "{:?}", v),
+        Value::Timestamp(v) => write!(out, "{:?}", v),
+        Value::Text(v) => write!(out, "{:?}", v),
+        Value::Binary(v) => {
+            write_binary(out, v);
+            Ok(())
+        }
+        Value::Opaque8(v) => {
+            write_binary(out, v);
+            Ok(())
+        }
+        Value::Opaque16(v) => {
+            write_binary(out, v);
+            Ok(())
+        }
+        Value::Opaque32(v) => {
+            write_binary(out, v);
+            Ok(())
+        }
+        Value::Opaque64(v) => {
+            write_binary(out, v);
+            Ok(())
+        }
+        Value::Struct(v) => {
+            write_message(out, schema, v, level);
+            Ok(())
+        }
+        Value::ArrayU16(v) => write!(out, "{:?}", v),
+        Value::ArrayU32(v) => write!(out, "{:?}", v),
+        Value::ArrayU64(v) => write!(out, "{:?}", v),
+        Value::ArrayI8(v) => write!(out, "{:?}", v),
+        Value::ArrayI16(v) => write!(out, "{:?}", v),
+        Value::ArrayI32(v) => write!(out, "{:?}", v),
+        Value::ArrayI64(v) => write!(out, "{:?}", v),
+        Value::ArrayF32(v) => write!(out, "{:?}", v),
+        Value::ArrayF64(v) => write!(out, "{:?}", v),
+        Value::ArrayText(v) => write!(out, "{:?}", v),
+        Value::ArrayBinary(v) => {
+            write_list(out, v, level, |out, v, _| write_binary(out, v));
+            Ok(())
+        }
+        Value::ArrayStruct(v) => {
+            write_list(out, v, level, |out, v, level| {
+                write_message(out, schema, v, level)
+            });
+            Ok(())
+        }
+    }
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let schema = Schema::new(vec![colfer_build::parse_schema(
+            "package demo\ntype node struct {\n\tname text\n\tdata binary\n\tchildren []node\n}\n",
+        )
+        .unwrap()]);
+        let mut child = DynamicMessage::new("demo.Node");
+        child.set("data", Value::Binary(vec![0x0a, 0xff]));
+        let mut root = DynamicMessage::new("demo.Node");
+        root.set(
+            "children",
+            Value::ArrayStruct(vec![child, DynamicMessage::new("demo.Node")]),
+        );
+        root.set("name", Value::Text("root".to_string()));
+
+        assert_eq!(
+            to_tree(&schema, &root),
+            "demo.Node {\n  name: \"root\"\n  children: [\n    demo.Node {\n      data: 0x0aff\n    }\n    demo.Node {}\n  ]\n}\n"
+        );
+    }
+}
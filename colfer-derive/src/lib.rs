@@ -0,0 +1,462 @@
+//! Derive macro for `colfer::Message`, re-exported by the `colfer` crate with the `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, LitInt, PathArguments, Type,
+};
+
+/// Derives `colfer::Message` for a struct with named fields.
+///
+/// Fields are numbered in declaration order, which can be overridden with
+/// `#[colfer(index = N)]`. A field without an explicit index follows the field before it, and
+/// deriving fails if two fields get the same index. Fields marked with `#[colfer(skip)]` are
+/// not encoded and decode to their default value.
+///
+/// Fields of type `Option<T>` or `Option<Box<T>>` are nested messages, and `Vec<T>` is a list
+/// of messages unless `T` is named like a list element of the runtime: an integer, `f32`,
+/// `f64`, `String`, `Vec<u8>`, `Arc<str>`, `Bytes` or `SmolStr`. Any other field type must
+/// implement `colfer::Type`. Mark a field with `#[colfer(type)]` to encode it with
+/// `colfer::Type` regardless, for example a list of an aliased element type, or with
+/// `#[colfer(messages)]` to encode it as a list of messages.
+#[proc_macro_derive(Message, attributes(colfer))]
+pub fn derive_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Type,
+    Message { boxed: bool },
+    Messages,
+}
+
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    local: Ident,
+    index: u8,
+    kind: Kind,
+}
+
+/// Returns the name and the first generic argument of a path type such as `Vec<T>`.
+fn split_generic(ty: &Type) -> Option<(&Ident, Option<&Type>)> {
+    let path = match ty {
+        Type::Path(path) if path.qself.is_none() => &path.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    let arg = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    };
+    Some((&segment.ident, arg))
+}
+
+fn field_kind(ty: &Type) -> Kind {
+    match split_generic(ty) {
+        Some((name, Some(inner))) if name == "Option" => Kind::Message {
+            boxed: matches!(split_generic(inner), Some((name, _)) if name == "Box"),
+        },
+        Some((name, Some(inner))) if name == "Vec" => match split_generic(inner) {
+            Some((name, _))
+                if [
+                    "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64", "String",
+                    "Vec", "Arc", "Bytes", "SmolStr",
+                ]
+                .iter()
+                .any(|ty| name == ty) =>
+            {
+                Kind::Type
+            }
+            _ => Kind::Messages,
+        },
+        _ => Kind::Type,
+    }
+}
+
+/// The `#[colfer(..)]` attributes of a field.
+#[derive(Default)]
+struct Attrs {
+    skip: bool,
+    index: Option<usize>,
+    kind: Option<Kind>,
+}
+
+fn field_attrs(field: &syn::Field) -> syn::Result<Attrs> {
+    let mut attrs = Attrs::default();
+    for attr in &field.attrs {
+        if !attr.path().is_ident("colfer") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else if meta.path.is_ident("index") {
+                attrs.index = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+            } else if meta.path.is_ident("type") {
+                attrs.kind = Some(Kind::Type);
+            } else if meta.path.is_ident("messages") {
+                attrs.kind = Some(Kind::Messages);
+            } else {
+                return Err(meta.error(
+                    "unknown colfer attribute, expected `index = N`, `skip`, `type` or `messages`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Returns the encoded fields by index, along with the skipped ones.
+fn parse_fields(
+    named: &Punctuated<syn::Field, Comma>,
+) -> syn::Result<(Vec<Field<'_>>, Vec<&Ident>)> {
+    let mut fields = Vec::new();
+    let mut skipped = Vec::new();
+    let mut next = 0;
+    for f in named {
+        let ident = f.ident.as_ref().unwrap();
+        let attrs = field_attrs(f)?;
+        if attrs.skip {
+            skipped.push(ident);
+            continue;
+        }
+        let index = attrs.index.unwrap_or(next);
+        if index >= 0x7f {
+            return Err(syn::Error::new_spanned(
+                f,
+                "colfer field indexes must be less than 127",
+            ));
+        }
+        if let Some(other) = fields.iter().find(|f: &&Field| f.index as usize == index) {
+            return Err(syn::Error::new_spanned(
+                f,
+                format!(
+                    "colfer field index {} is already used by field `{}`",
+                    index, other.ident
+                ),
+            ));
+        }
+        next = index + 1;
+        fields.push(Field {
+            ident,
+            ty: &f.ty,
+            local: format_ident!("__field{}", fields.len()),
+            index: index as u8,
+            kind: attrs.kind.unwrap_or_else(|| field_kind(&f.ty)),
+        });
+    }
+    fields.sort_by_key(|f| f.index);
+    Ok((fields, skipped))
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => Some(&named.named),
+            _ => None,
+        },
+        _ => None,
+    }
+    .ok_or_else(|| {
+        syn::Error::new_spanned(
+            input,
+            "`Message` can only be derived for structs with named fields",
+        )
+    })?;
+
+    let (fields, skipped) = parse_fields(named)?;
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let encode = fields.iter().map(|f| {
+        let Field {
+            ident, index, kind, ..
+        } = f;
+        match kind {
+            Kind::Type => quote! { ::colfer::Type::encode(&self.#ident, w, #index)?; },
+            Kind::Message { boxed: true } => {
+                quote! { ::colfer::encode_message(w, #index, self.#ident.as_deref())?; }
+            }
+            Kind::Message { boxed: false } => {
+                quote! { ::colfer::encode_message(w, #index, self.#ident.as_ref())?; }
+            }
+            Kind::Messages => quote! { ::colfer::encode_messages(w, #index, &self.#ident)?; },
+        }
+    });
+
+    let size = fields.iter().map(|f| {
+        let Field { ident, kind, .. } = f;
+        match kind {
+            Kind::Type => quote! { size += ::colfer::Type::size(&self.#ident); },
+            Kind::Message { boxed: true } => {
+                quote! { size += ::colfer::message_size(self.#ident.as_deref()); }
+            }
+            Kind::Message { boxed: false } => {
+                quote! { size += ::colfer::message_size(self.#ident.as_ref()); }
+            }
+            Kind::Messages => quote! { size += ::colfer::messages_size(&self.#ident); },
+        }
+    });
+
+    let locals = fields.iter().map(|f| {
+        let local = &f.local;
+        quote! { let mut #local = ::core::default::Default::default(); }
+    });
+
+    let decode = fields.iter().map(|f| {
+        let Field {
+            ident,
+            local,
+            index,
+            kind,
+            ..
+        } = f;
+        let field_str = ident.to_string().trim_start_matches("r#").to_string();
+        let context = quote! { .map_err(|err| err.with_field(#name_str, #field_str))? };
+        let value = match kind {
+            Kind::Type => quote! { ::colfer::Type::decode(r, flag, limits)#context },
+            Kind::Message { boxed: true } => quote! {
+                ::core::option::Option::Some(::std::boxed::Box::new(
+                    ::colfer::decode_message(r, limits)#context
+                ))
+            },
+            Kind::Message { boxed: false } => quote! {
+                ::core::option::Option::Some(::colfer::decode_message(r, limits)#context)
+            },
+            Kind::Messages => quote! { ::colfer::decode_messages(r, limits)#context },
+        };
+        quote! {
+            if id == #index {
+                #local = #value;
+                let next = ::colfer::read_header(r).map_err(|err| err.with_struct(#name_str))?;
+                id = next.0;
+                flag = next.1;
+            }
+        }
+    });
+
+    // Generic parameters can not be used in the constant table, so those messages are scanned
+    // by decoding them.
+    let shapes = if input.generics.params.is_empty() {
+        let table = shape_table(&fields);
+        quote! {
+            #[inline]
+            fn shapes() -> ::core::option::Option<&'static [::colfer::Shape]> {
+                const SHAPES: &[::colfer::Shape] = &[#(#table),*];
+                ::core::option::Option::Some(SHAPES)
+            }
+        }
+    } else {
+        TokenStream2::new()
+    };
+
+    let init = fields
+        .iter()
+        .map(|f| {
+            let Field { ident, local, .. } = f;
+            quote! { #ident: #local }
+        })
+        .chain(
+            skipped
+                .iter()
+                .map(|ident| quote! { #ident: ::core::default::Default::default() }),
+        );
+
+    Ok(quote! {
+        impl #impl_generics ::colfer::Message for #name #ty_generics #where_clause {
+            #[inline]
+            fn encode<W: ::std::io::Write>(&self, w: &mut W) -> ::colfer::Result<()> {
+                #(#encode)*
+                ::colfer::write_end(w)?;
+                ::core::result::Result::Ok(())
+            }
+
+            #[inline]
+            #[allow(unused_mut, unused_assignments, unused_variables)]
+            fn decode_fields<R: ::std::io::Read>(
+                r: &mut R,
+                limits: &::colfer::DecodeLimits,
+            ) -> ::colfer::Result<Self> {
+                #(#locals)*
+                let (mut id, mut flag) =
+                    ::colfer::read_header(r).map_err(|err| err.with_struct(#name_str))?;
+                #(#decode)*
+                ::core::result::Result::Ok(Self { #(#init,)* })
+            }
+
+            #[inline]
+            fn size(&self) -> usize {
+                let mut size = 1;
+                #(#size)*
+                size
+            }
+
+            #shapes
+        }
+    })
+}
+
+/// Returns the shapes of `fields` by index, with `Unknown` for the unused indexes.
+fn shape_table(fields: &[Field]) -> Vec<TokenStream2> {
+    let mut table =
+        vec![quote!(::colfer::Shape::Unknown); fields.last().map_or(0, |f| f.index as usize + 1)];
+    for f in fields {
+        table[f.index as usize] = field_shape(f.ty, &f.kind);
+    }
+    table
+}
+
+/// Returns the shape of a field of type `ty`, for scanning serials.
+fn field_shape(ty: &Type, kind: &Kind) -> TokenStream2 {
+    // The message type is the innermost generic argument of `Option<T>`, `Option<Box<T>>`
+    // and `Vec<T>`.
+    let mut inner = ty;
+    if !matches!(kind, Kind::Type) {
+        while let Some((_, Some(arg))) = split_generic(inner) {
+            inner = arg;
+        }
+    }
+    match kind {
+        Kind::Type => quote!(<#ty as ::colfer::Type>::SHAPE),
+        Kind::Message { .. } => {
+            quote!(::colfer::Shape::Message(<#inner as ::colfer::Message>::shapes))
+        }
+        Kind::Messages => {
+            quote!(::colfer::Shape::Messages(<#inner as ::colfer::Message>::shapes))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_err(input: DeriveInput) -> String {
+        expand(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            expand_err(syn::parse_quote! {
+                struct S {
+                    a: u32,
+                    #[colfer(index = 0)]
+                    b: u32,
+                }
+            }),
+            "colfer field index 0 is already used by field `a`"
+        );
+        assert_eq!(
+            expand_err(syn::parse_quote! {
+                struct S {
+                    #[colfer(index = 1)]
+                    a: u32,
+                    #[colfer(index = 0)]
+                    b: u32,
+                    c: u32,
+                }
+            }),
+            "colfer field index 1 is already used by field `a`"
+        );
+        assert_eq!(
+            expand_err(syn::parse_quote! {
+                struct S {
+                    #[colfer(index = 127)]
+                    a: u32,
+                }
+            }),
+            "colfer field indexes must be less than 127"
+        );
+        assert_eq!(
+            expand_err(syn::parse_quote! {
+                struct S {
+                    #[colfer(rename = "b")]
+                    a: u32,
+                }
+            }),
+            "unknown colfer attribute, expected `index = N`, `skip`, `type` or `messages`"
+        );
+    }
+
+    /// Returns the index and the shape of every encoded field of `input`.
+    fn layout(input: DeriveInput) -> Vec<(String, u8, String)> {
+        let named = match input.data {
+            Data::Struct(syn::DataStruct {
+                fields: Fields::Named(named),
+                ..
+            }) => named.named,
+            _ => unreachable!(),
+        };
+        let (fields, _) = parse_fields(&named).unwrap();
+        let table = shape_table(&fields);
+        fields
+            .iter()
+            .map(|f| {
+                let shape = table[f.index as usize].to_string().replace(' ', "");
+                (f.ident.to_string(), f.index, shape)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_indexes() {
+        let layout = layout(syn::parse_quote! {
+            struct S {
+                a: u32,
+                #[colfer(skip)]
+                cached: u32,
+                #[colfer(index = 5)]
+                b: Option<Box<S>>,
+                c: Vec<S>,
+                #[colfer(index = 1)]
+                d: Vec<bytes::Bytes>,
+                e: Vec<Arc<str>>,
+                #[colfer(index = 7, type)]
+                f: Vec<Name>,
+                #[colfer(messages)]
+                g: Vec<Node>,
+            }
+        });
+        let shape = |ty: &str| format!("<{}as::colfer::Type>::SHAPE", ty);
+        assert_eq!(
+            layout,
+            vec![
+                ("a".to_string(), 0, shape("u32")),
+                ("d".to_string(), 1, shape("Vec<bytes::Bytes>")),
+                ("e".to_string(), 2, shape("Vec<Arc<str>>")),
+                (
+                    "b".to_string(),
+                    5,
+                    "::colfer::Shape::Message(<Sas::colfer::Message>::shapes)".to_string()
+                ),
+                (
+                    "c".to_string(),
+                    6,
+                    "::colfer::Shape::Messages(<Sas::colfer::Message>::shapes)".to_string()
+                ),
+                ("f".to_string(), 7, shape("Vec<Name>")),
+                (
+                    "g".to_string(),
+                    8,
+                    "::colfer::Shape::Messages(<Nodeas::colfer::Message>::shapes)".to_string()
+                ),
+            ]
+        );
+    }
+}
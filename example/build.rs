@@ -1,9 +1,112 @@
+use std::fmt::Write;
+
+use colfer_build::ast::{Colfer, Struct};
+use colfer_build::CodeGenerator;
+
+/// Adds the schema names of the fields of every struct as `FIELD_NAMES`.
+struct FieldNames;
+
+impl CodeGenerator for FieldNames {
+    fn generate_struct(
+        &self,
+        _colfer: &Colfer,
+        s: &Struct,
+        code: &mut String,
+    ) -> anyhow::Result<()> {
+        let names: Vec<_> = s
+            .fields
+            .iter()
+            .map(|f| format!("\"{}\"", f.schema_name))
+            .collect();
+        writeln!(code, "impl {} {{", s.name)?;
+        writeln!(code, "\t/// The schema names of the fields.")?;
+        writeln!(
+            code,
+            "\tpub const FIELD_NAMES: &[&str] = &[{}];",
+            names.join(", ")
+        )?;
+        writeln!(code, "}}")?;
+        writeln!(code)?;
+        Ok(())
+    }
+}
+
 fn main() {
     colfer_build::Config::default()
         .out_dir("./src")
+        .views(true)
+        .serde(true)
         .compile(&["test.colf", "bench.colf"])
         .unwrap();
 
+    std::fs::create_dir_all("./src/strict").unwrap();
+    colfer_build::Config::default()
+        .out_dir("./src/strict")
+        .strict(true)
+        .derive_eq(true)
+        .compile(&["test.colf"])
+        .unwrap();
+
+    std::fs::create_dir_all("./src/lenient").unwrap();
+    colfer_build::Config::default()
+        .out_dir("./src/lenient")
+        .retain_unknown_fields(true)
+        .field_visibility("pub(crate)")
+        .compile(&["compat.colf"])
+        .unwrap();
+
+    std::fs::create_dir_all("./src/mapped").unwrap();
+    colfer_build::Config::default()
+        .out_dir("./src/mapped")
+        .map_type("binary", "bytes::Bytes")
+        .map_type("text", "std::sync::Arc<str>")
+        .map_type("timestamp", "chrono::DateTime<chrono::Utc>")
+        .map_type("[]float64", "smallvec::SmallVec<[{}; 4]>")
+        .map_field("gen.o.o", "Option<std::sync::Arc<O>>")
+        .map_field("gen.o.ss", "smallvec::SmallVec<[{}; 2]>")
+        .compile(&["test.colf"])
+        .unwrap();
+
+    std::fs::create_dir_all("./src/multi").unwrap();
+    colfer_build::Config::default()
+        .out_dir("./src/multi")
+        .views(true)
+        .derive_eq(true)
+        .derive_hash(true)
+        .derive_partial_ord(true)
+        .type_attribute("shop", "#[non_exhaustive]")
+        .field_attribute("shop.item.quantity", "#[doc(alias = \"count\")]")
+        .include_file(true)
+        .compile(&[
+            "multi/shop_order.colf",
+            "multi/shop_item.colf",
+            "multi/people.colf",
+        ])
+        .unwrap();
+
+    // Included from `OUT_DIR` with `colfer::include_schema!`.
+    colfer_build::Config::default()
+        .code_generator(FieldNames)
+        .include_file(true)
+        .compile(&[
+            "multi/shop_order.colf",
+            "multi/shop_item.colf",
+            "multi/people.colf",
+        ])
+        .unwrap();
+
+    std::fs::create_dir_all("./ts").unwrap();
+    colfer_build::Config::default()
+        .out_dir("./ts")
+        .generator(colfer_build::TypeScriptGenerator)
+        .compile(&[
+            "test.colf",
+            "multi/shop_order.colf",
+            "multi/shop_item.colf",
+            "multi/people.colf",
+        ])
+        .unwrap();
+
     prost_build::Config::default()
         .out_dir("./src")
         .compile_protos(&["bench.proto"], &["./"])
@@ -1,93 +1,159 @@
-#![allow(unused_variables, unused_assignments, unused_mut, unused_imports)]
-
-use std::io::{Write, Read, Result};
-
-use colfer::{Message, Type, DateTime};
-
-#[derive(Default, Clone, Debug, PartialEq)]
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    PartialEq,
+    colfer::serde::Serialize,
+    colfer::serde::Deserialize
+)]
+#[serde(crate = "colfer::serde", default)]
 pub struct Colfer {
-	pub key: i64,
-	pub host: String,
-	pub port: u16,
-	pub size: i64,
-	pub hash: u64,
-	pub ratio: f64,
-	pub route: bool,
+    pub key: i64,
+    pub host: String,
+    pub port: u16,
+    pub size: i64,
+    pub hash: u64,
+    pub ratio: f64,
+    pub route: bool,
+}
+impl colfer::Message for Colfer {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.key, w, 0)?;
+        colfer::Type::encode(&self.host, w, 1)?;
+        colfer::Type::encode(&self.port, w, 2)?;
+        colfer::Type::encode(&self.size, w, 3)?;
+        colfer::Type::encode(&self.hash, w, 4)?;
+        colfer::Type::encode(&self.ratio, w, 5)?;
+        colfer::Type::encode(&self.route, w, 6)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r)
+            .map_err(|err| err.with_struct("Colfer"))?;
+        if header.0 == 0 {
+            obj.key = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "key"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 1 {
+            obj.host = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "host"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 2 {
+            obj.port = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "port"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 3 {
+            obj.size = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "size"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 4 {
+            obj.hash = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "hash"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 5 {
+            obj.ratio = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "ratio"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 6 {
+            obj.route = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "route"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.key);
+        size += colfer::Type::size(&self.host);
+        size += colfer::Type::size(&self.port);
+        size += colfer::Type::size(&self.size);
+        size += colfer::Type::size(&self.hash);
+        size += colfer::Type::size(&self.ratio);
+        size += colfer::Type::size(&self.route);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <i64 as colfer::Type>::SHAPE,
+            <String as colfer::Type>::SHAPE,
+            <u16 as colfer::Type>::SHAPE,
+            <i64 as colfer::Type>::SHAPE,
+            <u64 as colfer::Type>::SHAPE,
+            <f64 as colfer::Type>::SHAPE,
+            <bool as colfer::Type>::SHAPE,
+        ];
+        Some(SHAPES)
+    }
+}
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct ColferView<'a> {
+    pub key: i64,
+    pub host: &'a str,
+    pub port: u16,
+    pub size: i64,
+    pub hash: u64,
+    pub ratio: f64,
+    pub route: bool,
 }
-
-impl Message for Colfer {
-	#[inline]
-	fn encode<W: Write>(&self, w: &mut W) -> Result<()> {
-		self.key.encode(w, 0)?;
-		self.host.encode(w, 1)?;
-		self.port.encode(w, 2)?;
-		self.size.encode(w, 3)?;
-		self.hash.encode(w, 4)?;
-		self.ratio.encode(w, 5)?;
-		self.route.encode(w, 6)?;
-		colfer::write_end(w)?;
-
-		Ok(())
-	}
-
-	#[inline]
-	fn decode<R: Read>(r: &mut R) -> Result<Self> {
-		let mut obj = Self::default();
-		let (mut id, mut flag) = colfer::read_header(r)?;
-		if id == 0 {
-			obj.key = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 1 {
-			obj.host = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 2 {
-			obj.port = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 3 {
-			obj.size = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 4 {
-			obj.hash = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 5 {
-			obj.ratio = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 6 {
-			obj.route = Type::decode(r, flag)?;
-		}
-
-		Ok(obj)
-	}
-
-	#[inline]
-	fn size(&self) -> usize {
-		let mut size = 0;
-		size += self.key.size();
-		size += self.host.size();
-		size += self.port.size();
-		size += self.size.size();
-		size += self.hash.size();
-		size += self.ratio.size();
-		size += self.route.size();
-		size
-	}
+impl<'a> colfer::MessageView<'a> for ColferView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(buf)
+            .map_err(|err| err.with_struct("Colfer"))?;
+        if header.0 == 0 {
+            obj.key = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "key"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 1 {
+            obj.host = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "host"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 2 {
+            obj.port = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "port"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 3 {
+            obj.size = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "size"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 4 {
+            obj.hash = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "hash"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 5 {
+            obj.ratio = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "ratio"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        if header.0 == 6 {
+            obj.route = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Colfer", "route"))?;
+            colfer::read_header(buf).map_err(|err| err.with_struct("Colfer"))?;
+        }
+        Ok(obj)
+    }
 }
-
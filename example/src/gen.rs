@@ -1,258 +1,587 @@
-#![allow(unused_variables, unused_assignments, unused_mut, unused_imports)]
-
-use std::io::{Write, Read, Result};
-
-use colfer::{Message, Type, DateTime};
-
-#[derive(Default, Clone, Debug, PartialEq)]
+/// O contains all supported data types.
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    PartialEq,
+    colfer::serde::Serialize,
+    colfer::serde::Deserialize
+)]
+#[serde(crate = "colfer::serde", default)]
 pub struct O {
-	pub b: bool,
-	pub u32: u32,
-	pub u64: u64,
-	pub i32: i32,
-	pub i64: i64,
-	pub f32: f32,
-	pub f64: f64,
-	pub t: DateTime,
-	pub s: String,
-	pub a: Vec<u8>,
-	pub o: Option<Box<O>>,
-	pub os: Vec<O>,
-	pub ss: Vec<String>,
-	pub r#as: Vec<Vec<u8>>,
-	pub u8: u8,
-	pub u16: u16,
-	pub f32s: Vec<f32>,
-	pub f64s: Vec<f64>,
+    /// B tests booleans.
+    pub b: bool,
+    /// U32 tests unsigned 32-bit integers.
+    pub u32: u32,
+    /// U64 tests unsigned 64-bit integers.
+    pub u64: u64,
+    /// I32 tests signed 32-bit integers.
+    pub i32: i32,
+    /// I64 tests signed 64-bit integers.
+    pub i64: i64,
+    /// F32 tests 32-bit floating points.
+    pub f32: f32,
+    /// F64 tests 64-bit floating points.
+    pub f64: f64,
+    /// T tests timestamps.
+    pub t: colfer::DateTime,
+    /// S tests text.
+    pub s: String,
+    /// A tests binaries.
+    pub a: Vec<u8>,
+    /// O tests nested data structures.
+    pub o: Option<Box<O>>,
+    /// Os tests data structure lists.
+    pub os: Vec<O>,
+    /// Ss tests text lists.
+    pub ss: Vec<String>,
+    /// As tests binary lists.
+    pub r#as: Vec<Vec<u8>>,
+    /// U8 tests unsigned 8-bit integers.
+    pub u8: u8,
+    /// U16 tests unsigned 16-bit integers.
+    pub u16: u16,
+    /// F32s tests 32-bit floating point lists.
+    pub f32s: Vec<f32>,
+    /// F64s tests 64-bit floating point lists.
+    pub f64s: Vec<f64>,
+    /// I8 tests signed 8-bit integers.
+    pub i8: i8,
+    /// I16 tests signed 16-bit integers.
+    pub i16: i16,
+    /// Op tests 64-bit opaque data.
+    pub op: [u8; 8],
+    /// U16s tests unsigned 16-bit integer lists.
+    pub u16s: Vec<u16>,
+    /// I32s tests signed 32-bit integer lists.
+    pub i32s: Vec<i32>,
+    /// U64s tests unsigned 64-bit integer lists.
+    pub u64s: Vec<u64>,
 }
-
-impl Message for O {
-	#[inline]
-	fn encode<W: Write>(&self, w: &mut W) -> Result<()> {
-		self.b.encode(w, 0)?;
-		self.u32.encode(w, 1)?;
-		self.u64.encode(w, 2)?;
-		self.i32.encode(w, 3)?;
-		self.i64.encode(w, 4)?;
-		self.f32.encode(w, 5)?;
-		self.f64.encode(w, 6)?;
-		self.t.encode(w, 7)?;
-		self.s.encode(w, 8)?;
-		self.a.encode(w, 9)?;
-		colfer::encode_message(w, 10, self.o.as_deref())?;
-		colfer::encode_messages(w, 11, &self.os)?;
-		self.ss.encode(w, 12)?;
-		self.r#as.encode(w, 13)?;
-		self.u8.encode(w, 14)?;
-		self.u16.encode(w, 15)?;
-		self.f32s.encode(w, 16)?;
-		self.f64s.encode(w, 17)?;
-		colfer::write_end(w)?;
-
-		Ok(())
-	}
-
-	#[inline]
-	fn decode<R: Read>(r: &mut R) -> Result<Self> {
-		let mut obj = Self::default();
-		let (mut id, mut flag) = colfer::read_header(r)?;
-		if id == 0 {
-			obj.b = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 1 {
-			obj.u32 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 2 {
-			obj.u64 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 3 {
-			obj.i32 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 4 {
-			obj.i64 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 5 {
-			obj.f32 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 6 {
-			obj.f64 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 7 {
-			obj.t = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 8 {
-			obj.s = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 9 {
-			obj.a = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 10 {
-			obj.o = colfer::decode_message(r)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 11 {
-			obj.os = colfer::decode_messages(r)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 12 {
-			obj.ss = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 13 {
-			obj.r#as = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 14 {
-			obj.u8 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 15 {
-			obj.u16 = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 16 {
-			obj.f32s = Type::decode(r, flag)?;
-			let next = colfer::read_header(r)?;
-			id = next.0;
-			flag = next.1;
-		}
-		if id == 17 {
-			obj.f64s = Type::decode(r, flag)?;
-		}
-
-		Ok(obj)
-	}
-
-	#[inline]
-	fn size(&self) -> usize {
-		let mut size = 0;
-		size += self.b.size();
-		size += self.u32.size();
-		size += self.u64.size();
-		size += self.i32.size();
-		size += self.i64.size();
-		size += self.f32.size();
-		size += self.f64.size();
-		size += self.t.size();
-		size += self.s.size();
-		size += self.a.size();
-		size += self.o.as_ref().map(|s| s.size()).unwrap_or_default();
-		size += self.os.iter().map(|s| s.size()).sum::<usize>();
-		size += self.ss.size();
-		size += self.r#as.size();
-		size += self.u8.size();
-		size += self.u16.size();
-		size += self.f32s.size();
-		size += self.f64s.size();
-		size
-	}
+impl colfer::Message for O {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.b, w, 0)?;
+        colfer::Type::encode(&self.u32, w, 1)?;
+        colfer::Type::encode(&self.u64, w, 2)?;
+        colfer::Type::encode(&self.i32, w, 3)?;
+        colfer::Type::encode(&self.i64, w, 4)?;
+        colfer::Type::encode(&self.f32, w, 5)?;
+        colfer::Type::encode(&self.f64, w, 6)?;
+        colfer::Type::encode(&self.t, w, 7)?;
+        colfer::Type::encode(&self.s, w, 8)?;
+        colfer::Type::encode(&self.a, w, 9)?;
+        colfer::encode_message(w, 10, self.o.as_deref())?;
+        colfer::encode_messages(w, 11, &self.os)?;
+        colfer::Type::encode(&self.ss, w, 12)?;
+        colfer::Type::encode(&self.r#as, w, 13)?;
+        colfer::Type::encode(&self.u8, w, 14)?;
+        colfer::Type::encode(&self.u16, w, 15)?;
+        colfer::Type::encode(&self.f32s, w, 16)?;
+        colfer::Type::encode(&self.f64s, w, 17)?;
+        colfer::Type::encode(&self.i8, w, 18)?;
+        colfer::Type::encode(&self.i16, w, 19)?;
+        colfer::Type::encode(&self.op, w, 20)?;
+        colfer::Type::encode(&self.u16s, w, 21)?;
+        colfer::Type::encode(&self.i32s, w, 22)?;
+        colfer::Type::encode(&self.u64s, w, 23)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        if header.0 == 0 {
+            obj.b = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "b"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 1 {
+            obj.u32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 2 {
+            obj.u64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 3 {
+            obj.i32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 4 {
+            obj.i64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 5 {
+            obj.f32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 6 {
+            obj.f64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 7 {
+            obj.t = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "t"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 8 {
+            obj.s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 9 {
+            obj.a = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "a"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 10 {
+            obj.o = Some(
+                Box::new(
+                    colfer::decode_message(r, limits)
+                        .map_err(|err| err.with_field("O", "o"))?,
+                ),
+            );
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 11 {
+            obj.os = colfer::decode_messages(r, limits)
+                .map_err(|err| err.with_field("O", "os"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 12 {
+            obj.ss = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "ss"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 13 {
+            obj.r#as = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "as"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 14 {
+            obj.u8 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u8"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 15 {
+            obj.u16 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 16 {
+            obj.f32s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 17 {
+            obj.f64s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 18 {
+            obj.i8 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i8"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 19 {
+            obj.i16 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i16"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 20 {
+            obj.op = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "op"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 21 {
+            obj.u16s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 22 {
+            obj.i32s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 23 {
+            obj.u64s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64s"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.b);
+        size += colfer::Type::size(&self.u32);
+        size += colfer::Type::size(&self.u64);
+        size += colfer::Type::size(&self.i32);
+        size += colfer::Type::size(&self.i64);
+        size += colfer::Type::size(&self.f32);
+        size += colfer::Type::size(&self.f64);
+        size += colfer::Type::size(&self.t);
+        size += colfer::Type::size(&self.s);
+        size += colfer::Type::size(&self.a);
+        size += colfer::message_size(self.o.as_deref());
+        size += colfer::messages_size(&self.os);
+        size += colfer::Type::size(&self.ss);
+        size += colfer::Type::size(&self.r#as);
+        size += colfer::Type::size(&self.u8);
+        size += colfer::Type::size(&self.u16);
+        size += colfer::Type::size(&self.f32s);
+        size += colfer::Type::size(&self.f64s);
+        size += colfer::Type::size(&self.i8);
+        size += colfer::Type::size(&self.i16);
+        size += colfer::Type::size(&self.op);
+        size += colfer::Type::size(&self.u16s);
+        size += colfer::Type::size(&self.i32s);
+        size += colfer::Type::size(&self.u64s);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <bool as colfer::Type>::SHAPE,
+            <u32 as colfer::Type>::SHAPE,
+            <u64 as colfer::Type>::SHAPE,
+            <i32 as colfer::Type>::SHAPE,
+            <i64 as colfer::Type>::SHAPE,
+            <f32 as colfer::Type>::SHAPE,
+            <f64 as colfer::Type>::SHAPE,
+            <colfer::DateTime as colfer::Type>::SHAPE,
+            <String as colfer::Type>::SHAPE,
+            <Vec<u8> as colfer::Type>::SHAPE,
+            colfer::Shape::Message(<O as colfer::Message>::shapes),
+            colfer::Shape::Messages(<O as colfer::Message>::shapes),
+            <Vec<String> as colfer::Type>::SHAPE,
+            <Vec<Vec<u8>> as colfer::Type>::SHAPE,
+            <u8 as colfer::Type>::SHAPE,
+            <u16 as colfer::Type>::SHAPE,
+            <Vec<f32> as colfer::Type>::SHAPE,
+            <Vec<f64> as colfer::Type>::SHAPE,
+            <i8 as colfer::Type>::SHAPE,
+            <i16 as colfer::Type>::SHAPE,
+            <[u8; 8] as colfer::Type>::SHAPE,
+            <Vec<u16> as colfer::Type>::SHAPE,
+            <Vec<i32> as colfer::Type>::SHAPE,
+            <Vec<u64> as colfer::Type>::SHAPE,
+        ];
+        Some(SHAPES)
+    }
 }
-
 #[derive(Default, Clone, Debug, PartialEq)]
+pub struct OView<'a> {
+    pub b: bool,
+    pub u32: u32,
+    pub u64: u64,
+    pub i32: i32,
+    pub i64: i64,
+    pub f32: f32,
+    pub f64: f64,
+    pub t: colfer::DateTime,
+    pub s: &'a str,
+    pub a: &'a [u8],
+    pub o: Option<Box<OView<'a>>>,
+    pub os: colfer::ListView<'a, OView<'a>>,
+    pub ss: colfer::ListView<'a, &'a str>,
+    pub r#as: colfer::ListView<'a, &'a [u8]>,
+    pub u8: u8,
+    pub u16: u16,
+    pub f32s: colfer::ListView<'a, f32>,
+    pub f64s: colfer::ListView<'a, f64>,
+    pub i8: i8,
+    pub i16: i16,
+    pub op: [u8; 8],
+    pub u16s: colfer::ListView<'a, u16>,
+    pub i32s: colfer::ListView<'a, i32>,
+    pub u64s: colfer::ListView<'a, u64>,
+}
+impl<'a> colfer::MessageView<'a> for OView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        if header.0 == 0 {
+            obj.b = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "b"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 1 {
+            obj.u32 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u32"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 2 {
+            obj.u64 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 3 {
+            obj.i32 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 4 {
+            obj.i64 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "i64"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 5 {
+            obj.f32 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 6 {
+            obj.f64 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 7 {
+            obj.t = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "t"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 8 {
+            obj.s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "s"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 9 {
+            obj.a = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "a"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 10 {
+            obj.o = Some(
+                Box::new(
+                    colfer::decode_message_view(buf, limits)
+                        .map_err(|err| err.with_field("O", "o"))?,
+                ),
+            );
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 11 {
+            obj.os = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "os"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 12 {
+            obj.ss = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "ss"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 13 {
+            obj.r#as = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "as"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 14 {
+            obj.u8 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u8"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 15 {
+            obj.u16 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 16 {
+            obj.f32s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32s"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 17 {
+            obj.f64s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64s"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 18 {
+            obj.i8 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "i8"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 19 {
+            obj.i16 = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "i16"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 20 {
+            obj.op = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "op"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 21 {
+            obj.u16s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16s"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 22 {
+            obj.i32s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32s"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 23 {
+            obj.u64s = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64s"))?;
+            colfer::read_header(buf).map_err(|err| err.with_struct("O"))?;
+        }
+        Ok(obj)
+    }
+}
+/// DromedaryCase oposes name casings.
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    PartialEq,
+    colfer::serde::Serialize,
+    colfer::serde::Deserialize
+)]
+#[serde(crate = "colfer::serde", default)]
 pub struct DromedaryCase {
-	pub pascal_case: String,
+    #[serde(rename = "PascalCase")]
+    pub pascal_case: String,
 }
-
-impl Message for DromedaryCase {
-	#[inline]
-	fn encode<W: Write>(&self, w: &mut W) -> Result<()> {
-		self.pascal_case.encode(w, 0)?;
-		colfer::write_end(w)?;
-
-		Ok(())
-	}
-
-	#[inline]
-	fn decode<R: Read>(r: &mut R) -> Result<Self> {
-		let mut obj = Self::default();
-		let (mut id, mut flag) = colfer::read_header(r)?;
-		if id == 0 {
-			obj.pascal_case = Type::decode(r, flag)?;
-		}
-
-		Ok(obj)
-	}
-
-	#[inline]
-	fn size(&self) -> usize {
-		let mut size = 0;
-		size += self.pascal_case.size();
-		size
-	}
+impl colfer::Message for DromedaryCase {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.pascal_case, w, 0)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(r)
+            .map_err(|err| err.with_struct("DromedaryCase"))?;
+        if header.0 == 0 {
+            obj.pascal_case = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("DromedaryCase", "pascal_case"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("DromedaryCase"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.pascal_case);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[<String as colfer::Type>::SHAPE];
+        Some(SHAPES)
+    }
 }
-
 #[derive(Default, Clone, Debug, PartialEq)]
+pub struct DromedaryCaseView<'a> {
+    pub pascal_case: &'a str,
+}
+impl<'a> colfer::MessageView<'a> for DromedaryCaseView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(buf)
+            .map_err(|err| err.with_struct("DromedaryCase"))?;
+        if header.0 == 0 {
+            obj.pascal_case = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("DromedaryCase", "pascal_case"))?;
+            colfer::read_header(buf).map_err(|err| err.with_struct("DromedaryCase"))?;
+        }
+        Ok(obj)
+    }
+}
+/// EmbedO has an inner object only.
+/// Covers regression of issue #66.
+#[derive(
+    Default,
+    Clone,
+    Debug,
+    PartialEq,
+    colfer::serde::Serialize,
+    colfer::serde::Deserialize
+)]
+#[serde(crate = "colfer::serde", default)]
 pub struct EmbedO {
-	pub inner: Option<Box<O>>,
+    pub inner: Option<O>,
+}
+impl colfer::Message for EmbedO {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::encode_message(w, 0, self.inner.as_ref())?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(r).map_err(|err| err.with_struct("EmbedO"))?;
+        if header.0 == 0 {
+            obj.inner = Some(
+                colfer::decode_message(r, limits)
+                    .map_err(|err| err.with_field("EmbedO", "inner"))?,
+            );
+            colfer::read_header(r).map_err(|err| err.with_struct("EmbedO"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::message_size(self.inner.as_ref());
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            colfer::Shape::Message(<O as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct EmbedOView<'a> {
+    pub inner: Option<OView<'a>>,
 }
-
-impl Message for EmbedO {
-	#[inline]
-	fn encode<W: Write>(&self, w: &mut W) -> Result<()> {
-		colfer::encode_message(w, 0, self.inner.as_deref())?;
-		colfer::write_end(w)?;
-
-		Ok(())
-	}
-
-	#[inline]
-	fn decode<R: Read>(r: &mut R) -> Result<Self> {
-		let mut obj = Self::default();
-		let (mut id, mut flag) = colfer::read_header(r)?;
-		if id == 0 {
-			obj.inner = colfer::decode_message(r)?;
-		}
-
-		Ok(obj)
-	}
-
-	#[inline]
-	fn size(&self) -> usize {
-		let mut size = 0;
-		size += self.inner.as_ref().map(|s| s.size()).unwrap_or_default();
-		size
-	}
+impl<'a> colfer::MessageView<'a> for EmbedOView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(buf).map_err(|err| err.with_struct("EmbedO"))?;
+        if header.0 == 0 {
+            obj.inner = Some(
+                colfer::decode_message_view(buf, limits)
+                    .map_err(|err| err.with_field("EmbedO", "inner"))?,
+            );
+            colfer::read_header(buf).map_err(|err| err.with_struct("EmbedO"))?;
+        }
+        Ok(obj)
+    }
 }
-
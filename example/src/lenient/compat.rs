@@ -0,0 +1,155 @@
+/// O has the first fields of gen.O only.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct O {
+    pub(crate) b: bool,
+    pub(crate) u32: u32,
+    pub(crate) u64: u64,
+    pub(crate) unknown_fields: Vec<u8>,
+}
+impl O {
+    #[inline]
+    fn decode_known<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<(Self, u8)> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        if header.0 == 0 {
+            obj.b = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "b"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 1 {
+            obj.u32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 2 {
+            obj.u64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        Ok((obj, header.0))
+    }
+}
+impl colfer::Message for O {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.b, w, 0)?;
+        colfer::Type::encode(&self.u32, w, 1)?;
+        colfer::Type::encode(&self.u64, w, 2)?;
+        std::io::Write::write_all(w, &self.unknown_fields)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let (obj, id) = Self::decode_known(r, limits)?;
+        colfer::check_end(id, 3).map_err(|err| err.with_struct("O"))?;
+        Ok(obj)
+    }
+    #[inline]
+    fn from_bytes_with_limits(
+        data: &[u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let (mut obj, unknown_fields) = colfer::from_bytes_unknown(
+            data,
+            limits,
+            Self::decode_known,
+        )?;
+        obj.unknown_fields = unknown_fields.to_vec();
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.b);
+        size += colfer::Type::size(&self.u32);
+        size += colfer::Type::size(&self.u64);
+        size += self.unknown_fields.len();
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <bool as colfer::Type>::SHAPE,
+            <u32 as colfer::Type>::SHAPE,
+            <u64 as colfer::Type>::SHAPE,
+        ];
+        Some(SHAPES)
+    }
+}
+/// EmbedO has an inner object only.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct EmbedO {
+    pub(crate) inner: Option<O>,
+    pub(crate) unknown_fields: Vec<u8>,
+}
+impl EmbedO {
+    #[inline]
+    fn decode_known<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<(Self, u8)> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r)
+            .map_err(|err| err.with_struct("EmbedO"))?;
+        if header.0 == 0 {
+            obj.inner = Some(
+                colfer::decode_message(r, limits)
+                    .map_err(|err| err.with_field("EmbedO", "inner"))?,
+            );
+            header = colfer::read_header(r).map_err(|err| err.with_struct("EmbedO"))?;
+        }
+        Ok((obj, header.0))
+    }
+}
+impl colfer::Message for EmbedO {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::encode_message(w, 0, self.inner.as_ref())?;
+        std::io::Write::write_all(w, &self.unknown_fields)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let (obj, id) = Self::decode_known(r, limits)?;
+        colfer::check_end(id, 1).map_err(|err| err.with_struct("EmbedO"))?;
+        Ok(obj)
+    }
+    #[inline]
+    fn from_bytes_with_limits(
+        data: &[u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let (mut obj, unknown_fields) = colfer::from_bytes_unknown(
+            data,
+            limits,
+            Self::decode_known,
+        )?;
+        obj.unknown_fields = unknown_fields.to_vec();
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::message_size(self.inner.as_ref());
+        size += self.unknown_fields.len();
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            colfer::Shape::Message(<O as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
@@ -1,15 +1,38 @@
-use colfer::Message;
+use colfer::{Message, MessageView};
 
 mod bench_colfer;
 mod bench_pb;
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "lenient/compat.rs"]
+mod compat;
+#[allow(dead_code)]
 mod gen;
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "mapped/gen.rs"]
+mod mapped_gen;
+#[cfg(test)]
+#[allow(dead_code)]
+mod included {
+    colfer::include_schema!();
+}
+#[cfg(test)]
+#[allow(dead_code)]
+mod multi {
+    include!("multi/_colfer_includes.rs");
+}
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "strict/gen.rs"]
+mod strict_gen;
 
 use prost::bytes::Bytes;
 use std::io::Cursor;
 use std::time::Instant;
 
 fn bench_colfer() {
-    use bench_colfer::Colfer;
+    use bench_colfer::{Colfer, ColferView};
 
     let test_data = vec![
         Colfer {
@@ -66,13 +89,25 @@ fn bench_colfer() {
     );
 
     let s = Instant::now();
-    let mut r = Cursor::new(data);
+    let mut r = Cursor::new(&data);
     for _ in 0..count {
         for _ in 0..4 {
             Colfer::decode(&mut r).unwrap();
         }
     }
     println!("COLFER decode: {:.03}s", (Instant::now() - s).as_secs_f32());
+
+    let s = Instant::now();
+    let mut buf = data.as_slice();
+    for _ in 0..count {
+        for _ in 0..4 {
+            ColferView::decode_fields(&mut buf, &Default::default()).unwrap();
+        }
+    }
+    println!(
+        "COLFER decode (view): {:.03}s",
+        (Instant::now() - s).as_secs_f32()
+    );
 }
 
 fn bench_pb() {
@@ -150,3 +185,766 @@ fn main() {
     bench_colfer();
     bench_pb();
 }
+
+#[cfg(test)]
+mod tests {
+    use colfer::{DecodeLimits, Error, ErrorKind, LimitError, Message, MessageView};
+
+    use crate::gen::{DromedaryCase, EmbedO, EmbedOView, OView, O};
+
+    fn limit_error(err: Error) -> Option<LimitError> {
+        match err.kind() {
+            ErrorKind::LimitExceeded(limit) => Some(*limit),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let obj = EmbedO {
+            inner: Some(O {
+                b: true,
+                u32: 1 << 30,
+                i64: -300,
+                s: "hello".to_string(),
+                a: vec![1, 2, 3],
+                o: Some(Box::new(O {
+                    u8: 8,
+                    ..O::default()
+                })),
+                i8: -8,
+                i16: i16::MIN,
+                op: [1, 2, 3, 4, 5, 6, 7, 8],
+                u16s: vec![1, u16::MAX],
+                i32s: vec![-1, i32::MAX],
+                u64s: vec![u64::MAX],
+                ..O::default()
+            }),
+        };
+        let data = obj.to_vec().unwrap();
+        assert_eq!(obj.size(), data.len());
+        assert_eq!(EmbedO::from_bytes(&data).unwrap(), obj);
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn with(f: impl FnOnce(&mut O)) -> O {
+        let mut obj = O::default();
+        f(&mut obj);
+        obj
+    }
+
+    fn check_golden(golden: Vec<(&str, O)>) {
+        for (serial, obj) in golden {
+            let data = hex(serial);
+            assert_eq!(obj.to_vec().unwrap(), data, "encode {:?}", obj);
+            assert_eq!(obj.size(), data.len(), "size {:?}", obj);
+            assert_eq!(O::from_bytes(&data).unwrap(), obj, "decode {}", serial);
+        }
+    }
+
+    #[test]
+    fn test_golden() {
+        // The first 18 fields of `O` are those of the test schema of the reference
+        // implementation, whose golden serials in `gen_test.go` of
+        // github.com/pascaldekloe/colfer these follow.
+        let t = |seconds, nano_seconds| colfer::DateTime {
+            seconds,
+            nano_seconds,
+        };
+        check_golden(vec![
+            ("7f", O::default()),
+            ("007f", with(|o| o.b = true)),
+            ("01017f", with(|o| o.u32 = 1)),
+            ("01ff017f", with(|o| o.u32 = u8::MAX as u32)),
+            ("01ffff037f", with(|o| o.u32 = u16::MAX as u32)),
+            ("81ffffffff7f", with(|o| o.u32 = u32::MAX)),
+            ("02017f", with(|o| o.u64 = 1)),
+            ("02ff017f", with(|o| o.u64 = u8::MAX as u64)),
+            ("02ffff037f", with(|o| o.u64 = u16::MAX as u64)),
+            ("02ffffffff0f7f", with(|o| o.u64 = u32::MAX as u64)),
+            ("82ffffffffffffffff7f", with(|o| o.u64 = u64::MAX)),
+            ("03017f", with(|o| o.i32 = 1)),
+            ("83017f", with(|o| o.i32 = -1)),
+            ("037f7f", with(|o| o.i32 = i8::MAX as i32)),
+            ("8380017f", with(|o| o.i32 = i8::MIN as i32)),
+            ("03ffff017f", with(|o| o.i32 = i16::MAX as i32)),
+            ("838080027f", with(|o| o.i32 = i16::MIN as i32)),
+            ("03ffffffff077f", with(|o| o.i32 = i32::MAX)),
+            ("8380808080087f", with(|o| o.i32 = i32::MIN)),
+            ("04017f", with(|o| o.i64 = 1)),
+            ("84017f", with(|o| o.i64 = -1)),
+            ("04ffffffff077f", with(|o| o.i64 = i32::MAX as i64)),
+            ("8480808080087f", with(|o| o.i64 = i32::MIN as i64)),
+            ("04ffffffffffffffff7f7f", with(|o| o.i64 = i64::MAX)),
+            ("848080808080808080807f", with(|o| o.i64 = i64::MIN)),
+            ("05000000017f", with(|o| o.f32 = f32::from_bits(1))),
+            ("057f7fffff7f", with(|o| o.f32 = f32::MAX)),
+            ("0600000000000000017f", with(|o| o.f64 = f64::from_bits(1))),
+            ("067fefffffffffffff7f", with(|o| o.f64 = f64::MAX)),
+            ("0755ef312a2e5da4e77f", with(|o| o.t = t(1441739050, 777888999))),
+            ("870000000100000000000003e87f", with(|o| o.t = t(1 << 32, 1000))),
+            ("87ffffffffffffffff2e5da4e77f", with(|o| o.t = t(-1, 777888999))),
+            ("0801417f", with(|o| o.s = "A".to_string())),
+            ("080261007f", with(|o| o.s = "a\0".to_string())),
+            ("0809c280e0a080f09080807f", with(|o| o.s = "\u{80}\u{800}\u{10000}".to_string())),
+            ("0901ff7f", with(|o| o.a = vec![u8::MAX])),
+            ("090202007f", with(|o| o.a = vec![2, 0])),
+            ("0a7f7f", with(|o| o.o = Some(Box::default()))),
+            ("0a007f7f", with(|o| o.o = Some(Box::new(with(|o| o.b = true))))),
+            ("0b01007f7f", with(|o| o.os = vec![with(|o| o.b = true)])),
+            ("0b027f7f7f", with(|o| o.os = vec![O::default(), O::default()])),
+            ("0c0300016101627f", with(|o| o.ss = vec!["".to_string(), "a".to_string(), "b".to_string()])),
+            ("0d0201000201027f", with(|o| o.r#as = vec![vec![0], vec![1, 2]])),
+            ("0e017f", with(|o| o.u8 = 1)),
+            ("0eff7f", with(|o| o.u8 = u8::MAX)),
+            ("8f017f", with(|o| o.u16 = 1)),
+            ("0fffff7f", with(|o| o.u16 = u16::MAX)),
+            ("1002000000003f8000007f", with(|o| o.f32s = vec![0.0, 1.0])),
+            ("11014058c000000000007f", with(|o| o.f64s = vec![99.0])),
+        ]);
+
+        // The other fields are colfer-rs extensions, which other colfer implementations can
+        // not read, so these serials only pin the encoding of this crate. They follow the rules
+        // of the types above: `int8` and `int16` like `int32`, opaque data like floating points,
+        // and integer lists like floating point lists.
+        check_golden(vec![
+            ("12017f", with(|o| o.i8 = 1)),
+            ("9280017f", with(|o| o.i8 = i8::MIN)),
+            ("13ffff017f", with(|o| o.i16 = i16::MAX)),
+            ("14000000000000ff017f", with(|o| o.op = [0, 0, 0, 0, 0, 0, 0xff, 1])),
+            ("1502000101007f", with(|o| o.u16s = vec![1, 256])),
+            ("1602ffffffff7fffffff7f", with(|o| o.i32s = vec![-1, i32::MAX])),
+            ("170100000000000000027f", with(|o| o.u64s = vec![2])),
+        ]);
+    }
+
+    #[test]
+    fn test_multi() {
+        use crate::multi::people::{Person, PersonView};
+        use crate::multi::shop::{Item, Order};
+
+        let order = Order {
+            id: 7,
+            customer: Some(Box::new(Person {
+                name: "Ada".to_string(),
+                last_order: Some(Box::new(Order {
+                    id: 6,
+                    ..Order::default()
+                })),
+            })),
+            items: vec![Item {
+                name: "pen".to_string(),
+                quantity: 2,
+                ..Item::default()
+            }],
+        };
+        let data = order.to_vec().unwrap();
+        assert_eq!(order.size(), data.len());
+        assert_eq!(Order::from_bytes(&data).unwrap(), order);
+        let included = crate::included::shop::Order::from_bytes(&data).unwrap();
+        assert_eq!(included.to_vec().unwrap(), data);
+
+        // Eq, Hash and PartialOrd are derived.
+        let orders: std::collections::HashSet<_> = vec![order.clone()].into_iter().collect();
+        assert!(orders.contains(&order));
+        assert!(
+            Order {
+                id: 6,
+                ..Order::default()
+            } < order
+        );
+
+        // Added by a code generator of the build script.
+        assert_eq!(
+            crate::included::shop::Order::FIELD_NAMES,
+            ["id", "customer", "items"]
+        );
+
+        let person = order.customer.unwrap();
+        let data = person.to_vec().unwrap();
+        let view = PersonView::from_bytes(&data).unwrap();
+        assert_eq!(view.name, "Ada");
+        assert_eq!(view.last_order.unwrap().id, 6);
+    }
+
+    #[test]
+    fn test_mapped() {
+        use crate::mapped_gen::O as MappedO;
+        use chrono::TimeZone;
+        use std::sync::Arc;
+
+        let obj = MappedO {
+            t: chrono::Utc.timestamp_opt(1_600_000_000, 42).unwrap(),
+            s: Arc::from("hello"),
+            a: bytes::Bytes::from_static(b"\x01\x02"),
+            o: Some(Arc::new(MappedO {
+                u32: 7,
+                ..MappedO::default()
+            })),
+            ss: smallvec::smallvec!["a".to_string(), "b".to_string()],
+            f64s: smallvec::smallvec![1.5, -2.0],
+            ..MappedO::default()
+        };
+        let data = obj.to_vec().unwrap();
+        assert_eq!(obj.size(), data.len());
+        assert_eq!(MappedO::from_bytes(&data).unwrap(), obj);
+
+        // Mapped types keep the wire format.
+        let plain = O::from_bytes(&data).unwrap();
+        assert_eq!(plain.s, "hello");
+        assert_eq!(plain.a, vec![1, 2]);
+        assert_eq!(plain.o.unwrap().u32, 7);
+        assert_eq!(plain.ss, vec!["a", "b"]);
+        assert_eq!(plain.f64s, vec![1.5, -2.0]);
+        assert_eq!(plain.t.seconds, 1_600_000_000);
+        assert_eq!(plain.t.nano_seconds, 42);
+    }
+
+    #[test]
+    fn test_limits() {
+        let obj = O {
+            s: "hello".to_string(),
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let limits = DecodeLimits {
+            max_size: 4,
+            ..DecodeLimits::default()
+        };
+        let err = O::from_bytes_with_limits(&data, &limits).unwrap_err();
+        assert_eq!(limit_error(err), Some(LimitError::Size));
+
+        let obj = O {
+            os: vec![O::default(), O::default()],
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let limits = DecodeLimits {
+            max_list_size: 1,
+            ..DecodeLimits::default()
+        };
+        let err = O::from_bytes_with_limits(&data, &limits).unwrap_err();
+        assert_eq!(limit_error(err), Some(LimitError::ListSize));
+
+        let mut obj = O::default();
+        for _ in 0..3 {
+            obj = O {
+                o: Some(Box::new(obj)),
+                ..O::default()
+            };
+        }
+        let data = obj.to_vec().unwrap();
+        let limits = DecodeLimits {
+            max_depth: 2,
+            ..DecodeLimits::default()
+        };
+        let err = O::from_bytes_with_limits(&data, &limits).unwrap_err();
+        assert_eq!(limit_error(err), Some(LimitError::Depth));
+        assert_eq!(O::from_bytes(&data).unwrap(), obj);
+    }
+
+    #[test]
+    fn test_error_context() {
+        let obj = EmbedO {
+            inner: Some(O {
+                s: "hello".to_string(),
+                ..O::default()
+            }),
+        };
+        let mut data = obj.to_vec().unwrap();
+        data[4] = 0xff;
+        let err = EmbedO::from_bytes(&data).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidUtf8(_)));
+        assert_eq!(err.struct_name(), Some("O"));
+        assert_eq!(err.field_name(), Some("s"));
+        assert_eq!(err.offset(), Some(8));
+
+        let err = EmbedO::from_bytes(&data[..5]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Truncated));
+        assert_eq!(err.field_name(), Some("s"));
+    }
+
+    #[test]
+    fn test_view() {
+        let obj = EmbedO {
+            inner: Some(O {
+                s: "hello".to_string(),
+                a: vec![1, 2, 3],
+                os: vec![
+                    O {
+                        i32: -7,
+                        ..O::default()
+                    },
+                    O::default(),
+                ],
+                ss: vec!["a".to_string(), "bc".to_string()],
+                r#as: vec![vec![4], vec![5, 6]],
+                f64s: vec![1.5, -2.0],
+                op: [0, 0, 0, 0, 0, 0, 0, 1],
+                i32s: vec![-3, 300],
+                ..O::default()
+            }),
+        };
+        let data = obj.to_vec().unwrap();
+        let view = EmbedOView::from_bytes(&data).unwrap();
+        let inner = view.inner.unwrap();
+        assert_eq!(inner.s, "hello");
+        assert_eq!(inner.a, &[1, 2, 3]);
+        assert_eq!(inner.os.len(), 2);
+        assert_eq!(inner.os.iter().next().unwrap().i32, -7);
+        assert_eq!(inner.ss.iter().collect::<Vec<_>>(), vec!["a", "bc"]);
+        assert_eq!(
+            inner.r#as.iter().collect::<Vec<_>>(),
+            vec![&[4][..], &[5, 6][..]]
+        );
+        assert_eq!(inner.f64s.iter().collect::<Vec<_>>(), vec![1.5, -2.0]);
+        assert_eq!(inner.op, [0, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(inner.i32s.iter().collect::<Vec<_>>(), vec![-3, 300]);
+
+        let err = OView::from_bytes(&data[..10]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Truncated));
+    }
+
+    #[test]
+    fn test_strict() {
+        use crate::strict_gen;
+
+        let obj = O {
+            b: true,
+            f64s: vec![1.0],
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let strict = strict_gen::O::from_bytes(&data).unwrap();
+        assert!(strict.b);
+        assert_eq!(strict.f64s, vec![1.0]);
+
+        // The end marker is consumed, so consecutive messages decode correctly.
+        let mut stream = data.clone();
+        stream.extend_from_slice(&data);
+        let mut r = std::io::Cursor::new(&stream);
+        assert_eq!(O::decode(&mut r).unwrap(), obj);
+        assert_eq!(O::decode(&mut r).unwrap(), obj);
+
+        let mut trailing = data.clone();
+        trailing.push(0);
+        assert_eq!(O::from_bytes(&trailing).unwrap(), obj);
+        let err = strict_gen::O::from_bytes(&trailing).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TrailingData));
+        assert_eq!(err.offset(), Some(data.len() as u64));
+
+        let err = strict_gen::O::from_bytes(&data[..data.len() - 1]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MissingEnd));
+
+        // Field ids out of order.
+        let err = strict_gen::O::from_bytes(&[14, 1, 0, 0x7f]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnexpectedField(0)));
+        assert_eq!(err.struct_name(), Some("O"));
+
+        let err = strict_gen::O::from_bytes(&[100, 0x7f]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnknownField(100)));
+        assert!(O::from_bytes(&[100, 0x7f]).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        use crate::compat;
+
+        let obj = O {
+            b: true,
+            u64: 64,
+            s: "hello".to_string(),
+            f64s: vec![1.0],
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let old = compat::O::from_bytes(&data).unwrap();
+        assert!(old.b);
+        assert_eq!(old.u64, 64);
+        assert!(!old.unknown_fields.is_empty());
+        assert_eq!(old.size(), data.len());
+        assert_eq!(old.to_vec().unwrap(), data);
+        assert_eq!(O::from_bytes(&old.to_vec().unwrap()).unwrap(), obj);
+
+        let known = compat::O {
+            u32: 32,
+            ..compat::O::default()
+        };
+        let data = known.to_vec().unwrap();
+        assert_eq!(compat::O::from_bytes(&data).unwrap(), known);
+
+        // Unknown fields of nested messages cannot be skipped.
+        let data = EmbedO { inner: Some(obj) }.to_vec().unwrap();
+        let err = compat::EmbedO::from_bytes(&data).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnknownField(8)));
+        assert_eq!(err.struct_name(), Some("O"));
+    }
+
+    #[test]
+    fn test_serde() {
+        let obj = EmbedO {
+            inner: Some(O {
+                b: true,
+                s: "hello".to_string(),
+                t: colfer::DateTime {
+                    seconds: 1,
+                    nano_seconds: 2,
+                },
+                o: Some(Box::new(O::default())),
+                i64: -300,
+                u16: 256,
+                a: vec![1, 2],
+                os: vec![O::default()],
+                ss: vec!["a".to_string()],
+                r#as: vec![vec![3]],
+                f32s: vec![1.5],
+                i16: -2,
+                op: [9; 8],
+                u64s: vec![1, 2],
+                ..O::default()
+            }),
+        };
+        let json = serde_json::to_string(&obj).unwrap();
+        assert_eq!(serde_json::from_str::<EmbedO>(&json).unwrap(), obj);
+
+        // The serde format matches the generated code.
+        let data = obj.to_vec().unwrap();
+        assert_eq!(colfer::to_vec(&obj).unwrap(), data);
+        assert_eq!(colfer::from_slice::<EmbedO>(&data).unwrap(), obj);
+
+        let obj = DromedaryCase {
+            pascal_case: "hello".to_string(),
+        };
+        let json = serde_json::to_string(&obj).unwrap();
+        assert_eq!(json, r#"{"PascalCase":"hello"}"#);
+        assert_eq!(
+            serde_json::from_str::<DromedaryCase>("{}").unwrap(),
+            DromedaryCase::default()
+        );
+    }
+
+    #[derive(colfer::Message, Default, Debug, PartialEq)]
+    struct Derived {
+        b: bool,
+        #[colfer(skip)]
+        cached: u32,
+        #[colfer(index = 9)]
+        a: Vec<u8>,
+        #[colfer(index = 8)]
+        s: String,
+        #[colfer(index = 10)]
+        o: Option<Box<Derived>>,
+        #[colfer(index = 11)]
+        os: Vec<Derived>,
+        #[colfer(index = 17)]
+        f64s: Vec<f64>,
+        #[colfer(index = 22)]
+        i32s: Vec<i32>,
+    }
+
+    #[test]
+    fn test_derive() {
+        let obj = O {
+            b: true,
+            s: "hello".to_string(),
+            a: vec![1, 2],
+            o: Some(Box::new(O {
+                b: true,
+                ..O::default()
+            })),
+            os: vec![O::default(), O::default()],
+            f64s: vec![0.5],
+            i32s: vec![-1],
+            ..O::default()
+        };
+        let derived = Derived {
+            b: true,
+            cached: 0,
+            s: "hello".to_string(),
+            a: vec![1, 2],
+            o: Some(Box::new(Derived {
+                b: true,
+                ..Derived::default()
+            })),
+            os: vec![Derived::default(), Derived::default()],
+            f64s: vec![0.5],
+            i32s: vec![-1],
+        };
+        let data = obj.to_vec().unwrap();
+        assert_eq!(derived.to_vec().unwrap(), data);
+        assert_eq!(derived.size(), data.len());
+        assert_eq!(Derived::from_bytes(&data).unwrap(), derived);
+
+        // Derived messages are scanned with their shapes as well.
+        let mut codec = colfer::codec::ColferCodec::<Derived>::new();
+        for i in 0..data.len() {
+            assert!(codec.decode_frame(&data[..i]).unwrap().is_none());
+        }
+        assert_eq!(
+            codec.decode_frame(&data).unwrap(),
+            Some((derived, data.len()))
+        );
+
+        let err = Derived::from_bytes(&data[..6]).unwrap_err();
+        assert_eq!(err.struct_name(), Some("Derived"));
+        assert_eq!(err.field_name(), Some("s"));
+    }
+
+    type Text = String;
+
+    #[derive(colfer::Message, Default, Debug, PartialEq)]
+    struct DerivedLists {
+        #[colfer(index = 12, type)]
+        ss: Vec<Text>,
+        r#as: Vec<bytes::Bytes>,
+    }
+
+    #[test]
+    fn test_derive_lists() {
+        let obj = O {
+            ss: vec!["a".to_string(), String::new()],
+            r#as: vec![vec![1, 2], vec![]],
+            ..O::default()
+        };
+        let derived = DerivedLists {
+            ss: obj.ss.clone(),
+            r#as: vec![bytes::Bytes::from_static(&[1, 2]), bytes::Bytes::new()],
+        };
+        let data = obj.to_vec().unwrap();
+        assert_eq!(derived.to_vec().unwrap(), data);
+        assert_eq!(DerivedLists::from_bytes(&data).unwrap(), derived);
+    }
+
+    #[test]
+    fn test_try_decode() {
+        let obj = O {
+            s: "hello".to_string(),
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let limits = DecodeLimits::default();
+        for i in 0..data.len() {
+            assert!(colfer::try_decode::<O>(&data[..i], &limits)
+                .unwrap()
+                .is_none());
+        }
+        let mut stream = data.clone();
+        stream.push(0);
+        assert_eq!(
+            colfer::try_decode::<O>(&stream, &limits).unwrap(),
+            Some((obj, data.len()))
+        );
+    }
+
+    #[test]
+    fn test_async() {
+        let obj = EmbedO {
+            inner: Some(O {
+                s: "hello".to_string(),
+                a: vec![1, 2, 3],
+                ..O::default()
+            }),
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let mut data = Vec::new();
+            colfer::encode_async(&mut data, &obj).await.unwrap();
+            colfer::encode_async(&mut data, &obj).await.unwrap();
+            assert_eq!(data.len(), 2 * obj.size());
+
+            // A small buffer makes the message arrive in pieces.
+            let mut r = tokio::io::BufReader::with_capacity(3, data.as_slice());
+            assert_eq!(
+                colfer::decode_async::<EmbedO, _>(&mut r).await.unwrap(),
+                obj
+            );
+            assert_eq!(
+                colfer::decode_async::<EmbedO, _>(&mut r).await.unwrap(),
+                obj
+            );
+            let err = colfer::decode_async::<EmbedO, _>(&mut r).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::Truncated));
+
+            let mut r = tokio::io::BufReader::new(&data[..data.len() - 1]);
+            colfer::decode_async::<EmbedO, _>(&mut r).await.unwrap();
+            let err = colfer::decode_async::<EmbedO, _>(&mut r).await.unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::Truncated));
+        });
+    }
+
+    #[test]
+    fn test_codec() {
+        use colfer::codec::ColferCodec;
+        use prost::bytes::BytesMut;
+        use tokio_util::codec::{Decoder, Encoder};
+
+        let objs = vec![
+            O {
+                s: "hello".to_string(),
+                ..O::default()
+            },
+            O::default(),
+            O {
+                os: vec![O::default()],
+                ..O::default()
+            },
+            // Every field, in its flagged form where there is one.
+            O {
+                b: true,
+                u32: u32::MAX,
+                u64: u64::MAX,
+                i32: -1,
+                i64: i64::MIN,
+                f32: 0.5,
+                f64: -0.5,
+                t: colfer::DateTime {
+                    seconds: -1,
+                    nano_seconds: 1,
+                },
+                s: "hello".to_string(),
+                a: vec![1, 2, 3],
+                o: Some(Box::new(O {
+                    u16: 1,
+                    ..O::default()
+                })),
+                os: vec![O::default(), O::default()],
+                ss: vec!["a".to_string(), String::new()],
+                r#as: vec![vec![1], vec![]],
+                u8: 8,
+                u16: u16::MAX,
+                f32s: vec![1.0, 2.0],
+                f64s: vec![3.0],
+                i8: -8,
+                i16: i16::MIN,
+                op: [1, 2, 3, 4, 5, 6, 7, 8],
+                u16s: vec![1, u16::MAX],
+                i32s: vec![-1, i32::MAX],
+                u64s: vec![u64::MAX],
+            },
+        ];
+
+        for length_prefixed in [false, true] {
+            let mut codec = ColferCodec::new().length_prefixed(length_prefixed);
+            let mut buf = BytesMut::new();
+            for obj in &objs {
+                codec.encode(obj.clone(), &mut buf).unwrap();
+            }
+            let data = buf.to_vec();
+
+            // Feed the stream byte by byte.
+            let mut src = BytesMut::new();
+            let mut decoded = Vec::new();
+            for b in &data {
+                src.extend_from_slice(&[*b]);
+                while let Some(obj) = codec.decode(&mut src).unwrap() {
+                    decoded.push(obj);
+                }
+            }
+            assert_eq!(decoded, objs);
+            assert!(src.is_empty());
+
+            let decoded = ColferCodec::new()
+                .length_prefixed(length_prefixed)
+                .iter(data.as_slice())
+                .collect::<colfer::Result<Vec<O>>>()
+                .unwrap();
+            assert_eq!(decoded, objs);
+
+            let mut iter = ColferCodec::<O>::new()
+                .length_prefixed(length_prefixed)
+                .iter(&data[..data.len() - 1]);
+            for _ in 1..objs.len() {
+                assert!(iter.next().unwrap().is_ok());
+            }
+            let err = iter.next().unwrap().unwrap_err();
+            assert!(matches!(err.kind(), ErrorKind::Truncated));
+            assert!(iter.next().is_none());
+        }
+
+        let limits = DecodeLimits {
+            max_size: 4,
+            ..DecodeLimits::default()
+        };
+        let mut codec = ColferCodec::<O>::new().length_prefixed(true).limits(limits);
+        let mut src = BytesMut::from(&[0, 0, 1, 0][..]);
+        let err = codec.decode(&mut src).unwrap_err();
+        assert_eq!(limit_error(err), Some(LimitError::Size));
+        let err = codec.encode(objs[0].clone(), &mut src).unwrap_err();
+        assert_eq!(limit_error(err), Some(LimitError::Size));
+    }
+
+    #[test]
+    fn test_dynamic() {
+        use colfer_build::dynamic::{DynamicMessage, Value};
+
+        let schema = colfer_build::ast::Schema::new(vec![colfer_build::parse_schema(
+            include_str!("../test.colf"),
+        )
+        .unwrap()]);
+        let obj = O {
+            b: true,
+            i64: -3,
+            s: "hello".to_string(),
+            o: Some(Box::new(O {
+                u16: 7,
+                ..O::default()
+            })),
+            f32s: vec![1.5],
+            i8: 1,
+            op: [2; 8],
+            u16s: vec![3],
+            ..O::default()
+        };
+        let data = obj.to_vec().unwrap();
+        let message = DynamicMessage::from_bytes(&schema, "O", &data).unwrap();
+        assert_eq!(message.get("b"), Some(&Value::Bool(true)));
+        assert_eq!(message.get("i64"), Some(&Value::I64(-3)));
+        assert_eq!(message.get("s"), Some(&Value::Text("hello".to_string())));
+        match message.get("o") {
+            Some(Value::Struct(inner)) => assert_eq!(inner.get("u16"), Some(&Value::U16(7))),
+            value => panic!("unexpected value {:?}", value),
+        }
+        assert_eq!(message.get("f32s"), Some(&Value::ArrayF32(vec![1.5])));
+        assert_eq!(message.get("i8"), Some(&Value::I8(1)));
+        assert_eq!(message.get("op"), Some(&Value::Opaque64([2; 8])));
+        assert_eq!(message.get("u16s"), Some(&Value::ArrayU16(vec![3])));
+        assert_eq!(message.to_vec(&schema).unwrap(), data);
+
+        let mut message = DynamicMessage::new("DromedaryCase");
+        message.set("PascalCase", Value::Text("hi".to_string()));
+        let data = message.to_vec(&schema).unwrap();
+        assert_eq!(DromedaryCase::from_bytes(&data).unwrap().pascal_case, "hi");
+
+        // Structs may reference the structs of other packages.
+        let schema = colfer_build::parse_schema_files(&[
+            "multi/shop_order.colf",
+            "multi/shop_item.colf",
+            "multi/people.colf",
+        ])
+        .unwrap();
+        let order = crate::multi::shop::Order {
+            id: 7,
+            customer: Some(Box::new(crate::multi::people::Person {
+                name: "Ada".to_string(),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let data = order.to_vec().unwrap();
+        let message = DynamicMessage::from_bytes(&schema, "Order", &data).unwrap();
+        assert_eq!(message.name(), "shop.Order");
+        match message.get("customer") {
+            Some(Value::Struct(inner)) => assert_eq!(inner.name(), "people.Person"),
+            value => panic!("unexpected value {:?}", value),
+        }
+        assert_eq!(message.to_vec(&schema).unwrap(), data);
+    }
+}
@@ -0,0 +1,356 @@
+/// O contains all supported data types.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct O {
+    /// B tests booleans.
+    pub b: bool,
+    /// U32 tests unsigned 32-bit integers.
+    pub u32: u32,
+    /// U64 tests unsigned 64-bit integers.
+    pub u64: u64,
+    /// I32 tests signed 32-bit integers.
+    pub i32: i32,
+    /// I64 tests signed 64-bit integers.
+    pub i64: i64,
+    /// F32 tests 32-bit floating points.
+    pub f32: f32,
+    /// F64 tests 64-bit floating points.
+    pub f64: f64,
+    /// T tests timestamps.
+    pub t: chrono::DateTime<chrono::Utc>,
+    /// S tests text.
+    pub s: std::sync::Arc<str>,
+    /// A tests binaries.
+    pub a: bytes::Bytes,
+    /// O tests nested data structures.
+    pub o: Option<std::sync::Arc<O>>,
+    /// Os tests data structure lists.
+    pub os: Vec<O>,
+    /// Ss tests text lists.
+    pub ss: smallvec::SmallVec<[String; 2]>,
+    /// As tests binary lists.
+    pub r#as: Vec<Vec<u8>>,
+    /// U8 tests unsigned 8-bit integers.
+    pub u8: u8,
+    /// U16 tests unsigned 16-bit integers.
+    pub u16: u16,
+    /// F32s tests 32-bit floating point lists.
+    pub f32s: Vec<f32>,
+    /// F64s tests 64-bit floating point lists.
+    pub f64s: smallvec::SmallVec<[f64; 4]>,
+    /// I8 tests signed 8-bit integers.
+    pub i8: i8,
+    /// I16 tests signed 16-bit integers.
+    pub i16: i16,
+    /// Op tests 64-bit opaque data.
+    pub op: [u8; 8],
+    /// U16s tests unsigned 16-bit integer lists.
+    pub u16s: Vec<u16>,
+    /// I32s tests signed 32-bit integer lists.
+    pub i32s: Vec<i32>,
+    /// U64s tests unsigned 64-bit integer lists.
+    pub u64s: Vec<u64>,
+}
+impl colfer::Message for O {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.b, w, 0)?;
+        colfer::Type::encode(&self.u32, w, 1)?;
+        colfer::Type::encode(&self.u64, w, 2)?;
+        colfer::Type::encode(&self.i32, w, 3)?;
+        colfer::Type::encode(&self.i64, w, 4)?;
+        colfer::Type::encode(&self.f32, w, 5)?;
+        colfer::Type::encode(&self.f64, w, 6)?;
+        colfer::Type::encode(&self.t, w, 7)?;
+        colfer::Type::encode(&self.s, w, 8)?;
+        colfer::Type::encode(&self.a, w, 9)?;
+        colfer::encode_message(w, 10, self.o.as_deref())?;
+        colfer::encode_messages(w, 11, &self.os)?;
+        colfer::Type::encode(&self.ss, w, 12)?;
+        colfer::Type::encode(&self.r#as, w, 13)?;
+        colfer::Type::encode(&self.u8, w, 14)?;
+        colfer::Type::encode(&self.u16, w, 15)?;
+        colfer::Type::encode(&self.f32s, w, 16)?;
+        colfer::Type::encode(&self.f64s, w, 17)?;
+        colfer::Type::encode(&self.i8, w, 18)?;
+        colfer::Type::encode(&self.i16, w, 19)?;
+        colfer::Type::encode(&self.op, w, 20)?;
+        colfer::Type::encode(&self.u16s, w, 21)?;
+        colfer::Type::encode(&self.i32s, w, 22)?;
+        colfer::Type::encode(&self.u64s, w, 23)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        if header.0 == 0 {
+            obj.b = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "b"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 1 {
+            obj.u32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 2 {
+            obj.u64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 3 {
+            obj.i32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 4 {
+            obj.i64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 5 {
+            obj.f32 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 6 {
+            obj.f64 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 7 {
+            obj.t = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "t"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 8 {
+            obj.s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 9 {
+            obj.a = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "a"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 10 {
+            obj.o = Some(
+                colfer::decode_message::<_, O>(r, limits)
+                    .map_err(|err| err.with_field("O", "o"))?
+                    .into(),
+            );
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 11 {
+            obj.os = colfer::decode_messages(r, limits)
+                .map_err(|err| err.with_field("O", "os"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 12 {
+            obj.ss = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "ss"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 13 {
+            obj.r#as = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "as"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 14 {
+            obj.u8 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u8"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 15 {
+            obj.u16 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 16 {
+            obj.f32s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f32s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 17 {
+            obj.f64s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "f64s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 18 {
+            obj.i8 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i8"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 19 {
+            obj.i16 = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i16"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 20 {
+            obj.op = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "op"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 21 {
+            obj.u16s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u16s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 22 {
+            obj.i32s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "i32s"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        if header.0 == 23 {
+            obj.u64s = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("O", "u64s"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("O"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.b);
+        size += colfer::Type::size(&self.u32);
+        size += colfer::Type::size(&self.u64);
+        size += colfer::Type::size(&self.i32);
+        size += colfer::Type::size(&self.i64);
+        size += colfer::Type::size(&self.f32);
+        size += colfer::Type::size(&self.f64);
+        size += colfer::Type::size(&self.t);
+        size += colfer::Type::size(&self.s);
+        size += colfer::Type::size(&self.a);
+        size += colfer::message_size(self.o.as_deref());
+        size += colfer::messages_size(&self.os);
+        size += colfer::Type::size(&self.ss);
+        size += colfer::Type::size(&self.r#as);
+        size += colfer::Type::size(&self.u8);
+        size += colfer::Type::size(&self.u16);
+        size += colfer::Type::size(&self.f32s);
+        size += colfer::Type::size(&self.f64s);
+        size += colfer::Type::size(&self.i8);
+        size += colfer::Type::size(&self.i16);
+        size += colfer::Type::size(&self.op);
+        size += colfer::Type::size(&self.u16s);
+        size += colfer::Type::size(&self.i32s);
+        size += colfer::Type::size(&self.u64s);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <bool as colfer::Type>::SHAPE,
+            <u32 as colfer::Type>::SHAPE,
+            <u64 as colfer::Type>::SHAPE,
+            <i32 as colfer::Type>::SHAPE,
+            <i64 as colfer::Type>::SHAPE,
+            <f32 as colfer::Type>::SHAPE,
+            <f64 as colfer::Type>::SHAPE,
+            <chrono::DateTime<chrono::Utc> as colfer::Type>::SHAPE,
+            <std::sync::Arc<str> as colfer::Type>::SHAPE,
+            <bytes::Bytes as colfer::Type>::SHAPE,
+            colfer::Shape::Message(<O as colfer::Message>::shapes),
+            colfer::Shape::Messages(<O as colfer::Message>::shapes),
+            <smallvec::SmallVec<[String; 2]> as colfer::Type>::SHAPE,
+            <Vec<Vec<u8>> as colfer::Type>::SHAPE,
+            <u8 as colfer::Type>::SHAPE,
+            <u16 as colfer::Type>::SHAPE,
+            <Vec<f32> as colfer::Type>::SHAPE,
+            <smallvec::SmallVec<[f64; 4]> as colfer::Type>::SHAPE,
+            <i8 as colfer::Type>::SHAPE,
+            <i16 as colfer::Type>::SHAPE,
+            <[u8; 8] as colfer::Type>::SHAPE,
+            <Vec<u16> as colfer::Type>::SHAPE,
+            <Vec<i32> as colfer::Type>::SHAPE,
+            <Vec<u64> as colfer::Type>::SHAPE,
+        ];
+        Some(SHAPES)
+    }
+}
+/// DromedaryCase oposes name casings.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct DromedaryCase {
+    pub pascal_case: std::sync::Arc<str>,
+}
+impl colfer::Message for DromedaryCase {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.pascal_case, w, 0)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(r)
+            .map_err(|err| err.with_struct("DromedaryCase"))?;
+        if header.0 == 0 {
+            obj.pascal_case = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("DromedaryCase", "pascal_case"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("DromedaryCase"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.pascal_case);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[<std::sync::Arc<str> as colfer::Type>::SHAPE];
+        Some(SHAPES)
+    }
+}
+/// EmbedO has an inner object only.
+/// Covers regression of issue #66.
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct EmbedO {
+    pub inner: Option<O>,
+}
+impl colfer::Message for EmbedO {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::encode_message(w, 0, self.inner.as_ref())?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let header = colfer::read_header(r).map_err(|err| err.with_struct("EmbedO"))?;
+        if header.0 == 0 {
+            obj.inner = Some(
+                colfer::decode_message(r, limits)
+                    .map_err(|err| err.with_field("EmbedO", "inner"))?,
+            );
+            colfer::read_header(r).map_err(|err| err.with_struct("EmbedO"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::message_size(self.inner.as_ref());
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            colfer::Shape::Message(<O as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
@@ -0,0 +1,6 @@
+pub mod shop {
+    include!("shop.rs");
+}
+pub mod people {
+    include!("people.rs");
+}
@@ -0,0 +1,84 @@
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, PartialOrd)]
+pub struct Person {
+    pub name: String,
+    pub last_order: Option<Box<super::shop::Order>>,
+}
+impl colfer::Message for Person {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.name, w, 0)?;
+        colfer::encode_message(w, 1, self.last_order.as_deref())?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r)
+            .map_err(|err| err.with_struct("Person"))?;
+        if header.0 == 0 {
+            obj.name = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Person", "name"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Person"))?;
+        }
+        if header.0 == 1 {
+            obj.last_order = Some(
+                Box::new(
+                    colfer::decode_message(r, limits)
+                        .map_err(|err| err.with_field("Person", "last_order"))?,
+                ),
+            );
+            colfer::read_header(r).map_err(|err| err.with_struct("Person"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.name);
+        size += colfer::message_size(self.last_order.as_deref());
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <String as colfer::Type>::SHAPE,
+            colfer::Shape::Message(<super::shop::Order as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct PersonView<'a> {
+    pub name: &'a str,
+    pub last_order: Option<Box<super::shop::OrderView<'a>>>,
+}
+impl<'a> colfer::MessageView<'a> for PersonView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(buf)
+            .map_err(|err| err.with_struct("Person"))?;
+        if header.0 == 0 {
+            obj.name = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Person", "name"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Person"))?;
+        }
+        if header.0 == 1 {
+            obj.last_order = Some(
+                Box::new(
+                    colfer::decode_message_view(buf, limits)
+                        .map_err(|err| err.with_field("Person", "last_order"))?,
+                ),
+            );
+            colfer::read_header(buf).map_err(|err| err.with_struct("Person"))?;
+        }
+        Ok(obj)
+    }
+}
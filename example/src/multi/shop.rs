@@ -0,0 +1,197 @@
+/// Order is placed by a customer of package people.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, PartialOrd)]
+#[non_exhaustive]
+pub struct Order {
+    pub id: u64,
+    pub customer: Option<Box<super::people::Person>>,
+    pub items: Vec<Item>,
+}
+impl colfer::Message for Order {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.id, w, 0)?;
+        colfer::encode_message(w, 1, self.customer.as_deref())?;
+        colfer::encode_messages(w, 2, &self.items)?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r).map_err(|err| err.with_struct("Order"))?;
+        if header.0 == 0 {
+            obj.id = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Order", "id"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Order"))?;
+        }
+        if header.0 == 1 {
+            obj.customer = Some(
+                Box::new(
+                    colfer::decode_message(r, limits)
+                        .map_err(|err| err.with_field("Order", "customer"))?,
+                ),
+            );
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Order"))?;
+        }
+        if header.0 == 2 {
+            obj.items = colfer::decode_messages(r, limits)
+                .map_err(|err| err.with_field("Order", "items"))?;
+            colfer::read_header(r).map_err(|err| err.with_struct("Order"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.id);
+        size += colfer::message_size(self.customer.as_deref());
+        size += colfer::messages_size(&self.items);
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <u64 as colfer::Type>::SHAPE,
+            colfer::Shape::Message(<super::people::Person as colfer::Message>::shapes),
+            colfer::Shape::Messages(<Item as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct OrderView<'a> {
+    pub id: u64,
+    pub customer: Option<Box<super::people::PersonView<'a>>>,
+    pub items: colfer::ListView<'a, ItemView<'a>>,
+}
+impl<'a> colfer::MessageView<'a> for OrderView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(buf)
+            .map_err(|err| err.with_struct("Order"))?;
+        if header.0 == 0 {
+            obj.id = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Order", "id"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Order"))?;
+        }
+        if header.0 == 1 {
+            obj.customer = Some(
+                Box::new(
+                    colfer::decode_message_view(buf, limits)
+                        .map_err(|err| err.with_field("Order", "customer"))?,
+                ),
+            );
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Order"))?;
+        }
+        if header.0 == 2 {
+            obj.items = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Order", "items"))?;
+            colfer::read_header(buf).map_err(|err| err.with_struct("Order"))?;
+        }
+        Ok(obj)
+    }
+}
+/// Item references a struct of the other file of the package.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, PartialOrd)]
+#[non_exhaustive]
+pub struct Item {
+    pub name: String,
+    #[doc(alias = "count")]
+    pub quantity: u32,
+    pub order: Option<Order>,
+}
+impl colfer::Message for Item {
+    #[inline]
+    fn encode<W: std::io::Write>(&self, w: &mut W) -> colfer::Result<()> {
+        colfer::Type::encode(&self.name, w, 0)?;
+        colfer::Type::encode(&self.quantity, w, 1)?;
+        colfer::encode_message(w, 2, self.order.as_ref())?;
+        colfer::write_end(w)?;
+        Ok(())
+    }
+    #[inline]
+    fn decode_fields<R: std::io::Read>(
+        r: &mut R,
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(r).map_err(|err| err.with_struct("Item"))?;
+        if header.0 == 0 {
+            obj.name = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Item", "name"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Item"))?;
+        }
+        if header.0 == 1 {
+            obj.quantity = colfer::Type::decode(r, header.1, limits)
+                .map_err(|err| err.with_field("Item", "quantity"))?;
+            header = colfer::read_header(r).map_err(|err| err.with_struct("Item"))?;
+        }
+        if header.0 == 2 {
+            obj.order = Some(
+                colfer::decode_message(r, limits)
+                    .map_err(|err| err.with_field("Item", "order"))?,
+            );
+            colfer::read_header(r).map_err(|err| err.with_struct("Item"))?;
+        }
+        Ok(obj)
+    }
+    #[inline]
+    fn size(&self) -> usize {
+        let mut size = 1;
+        size += colfer::Type::size(&self.name);
+        size += colfer::Type::size(&self.quantity);
+        size += colfer::message_size(self.order.as_ref());
+        size
+    }
+    #[inline]
+    fn shapes() -> Option<&'static [colfer::Shape]> {
+        const SHAPES: &[colfer::Shape] = &[
+            <String as colfer::Type>::SHAPE,
+            <u32 as colfer::Type>::SHAPE,
+            colfer::Shape::Message(<Order as colfer::Message>::shapes),
+        ];
+        Some(SHAPES)
+    }
+}
+#[derive(Default, Clone, Debug, PartialEq)]
+pub struct ItemView<'a> {
+    pub name: &'a str,
+    pub quantity: u32,
+    pub order: Option<OrderView<'a>>,
+}
+impl<'a> colfer::MessageView<'a> for ItemView<'a> {
+    #[inline]
+    fn decode_fields(
+        buf: &mut &'a [u8],
+        limits: &colfer::DecodeLimits,
+    ) -> colfer::Result<Self> {
+        let mut obj = Self::default();
+        let mut header = colfer::read_header(buf)
+            .map_err(|err| err.with_struct("Item"))?;
+        if header.0 == 0 {
+            obj.name = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Item", "name"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Item"))?;
+        }
+        if header.0 == 1 {
+            obj.quantity = colfer::TypeView::decode_view(buf, header.1, limits)
+                .map_err(|err| err.with_field("Item", "quantity"))?;
+            header = colfer::read_header(buf).map_err(|err| err.with_struct("Item"))?;
+        }
+        if header.0 == 2 {
+            obj.order = Some(
+                colfer::decode_message_view(buf, limits)
+                    .map_err(|err| err.with_field("Item", "order"))?,
+            );
+            colfer::read_header(buf).map_err(|err| err.with_struct("Item"))?;
+        }
+        Ok(obj)
+    }
+}
@@ -0,0 +1,396 @@
+// Code generated by colfer-build from package people. DO NOT EDIT.
+
+import * as shop from "./shop";
+
+/** A timestamp with nanosecond precision. */
+export interface DateTime {
+	/** Seconds since January 1, 1970 UTC. */
+	seconds: bigint;
+	/** Nanoseconds since the last second boundary. */
+	nanos: number;
+}
+
+function checkRange(x: number, min: number, max: number): number {
+	if (!Number.isInteger(x) || x < min || x > max) {
+		throw new RangeError(`colfer: ${x} is out of range`);
+	}
+	return x;
+}
+
+function checkBigRange(x: bigint, min: bigint, max: bigint): bigint {
+	if (x < min || x > max) {
+		throw new RangeError(`colfer: ${x} is out of range`);
+	}
+	return x;
+}
+
+function signedMin(bits: bigint): bigint {
+	return -(1n << (bits - 1n));
+}
+
+function signedMax(bits: bigint): bigint {
+	return (1n << (bits - 1n)) - 1n;
+}
+
+/** @internal */
+export class ColferWriter {
+	buf = new Uint8Array(256);
+	len = 0;
+
+	finish(): Uint8Array {
+		return this.buf.slice(0, this.len);
+	}
+
+	reserve(n: number): DataView {
+		if (this.len + n > this.buf.length) {
+			const buf = new Uint8Array(Math.max(this.buf.length * 2, this.len + n));
+			buf.set(this.buf.subarray(0, this.len));
+			this.buf = buf;
+		}
+		const view = new DataView(this.buf.buffer, this.len, n);
+		this.len += n;
+		return view;
+	}
+
+	byte(b: number): void {
+		this.reserve(1).setUint8(0, b);
+	}
+
+	raw(data: Uint8Array): void {
+		const start = this.len;
+		this.reserve(data.length);
+		this.buf.set(data, start);
+	}
+
+	varint(x: bigint): void {
+		for (let i = 0; i < 8 && x >= 0x80n; i++) {
+			this.byte(Number(x & 0x7fn) | 0x80);
+			x >>= 7n;
+		}
+		this.byte(Number(x));
+	}
+
+	bool(id: number, x: boolean): void {
+		if (x) {
+			this.byte(id);
+		}
+	}
+
+	uint8(id: number, x: number): void {
+		if (checkRange(x, 0, 0xff) !== 0) {
+			this.byte(id);
+			this.byte(x);
+		}
+	}
+
+	uint16(id: number, x: number): void {
+		if (checkRange(x, 0, 0xffff) >= 0x100) {
+			this.byte(id);
+			this.reserve(2).setUint16(0, x);
+		} else if (x !== 0) {
+			this.byte(id | 0x80);
+			this.byte(x);
+		}
+	}
+
+	uint32(id: number, x: number): void {
+		if (checkRange(x, 0, 0xffffffff) >= 1 << 21) {
+			this.byte(id | 0x80);
+			this.reserve(4).setUint32(0, x);
+		} else if (x !== 0) {
+			this.byte(id);
+			this.varint(BigInt(x));
+		}
+	}
+
+	uint64(id: number, x: bigint): void {
+		if (checkBigRange(x, 0n, (1n << 64n) - 1n) >= 1n << 49n) {
+			this.byte(id | 0x80);
+			this.reserve(8).setBigUint64(0, x);
+		} else if (x !== 0n) {
+			this.byte(id);
+			this.varint(x);
+		}
+	}
+
+	int(id: number, x: bigint, bits: bigint): void {
+		if (checkBigRange(x, signedMin(bits), signedMax(bits)) > 0n) {
+			this.byte(id);
+			this.varint(x);
+		} else if (x < 0n) {
+			this.byte(id | 0x80);
+			this.varint(-x);
+		}
+	}
+
+	float32(id: number, x: number): void {
+		if (x !== 0) {
+			this.byte(id);
+			this.reserve(4).setFloat32(0, x);
+		}
+	}
+
+	float64(id: number, x: number): void {
+		if (x !== 0) {
+			this.byte(id);
+			this.reserve(8).setFloat64(0, x);
+		}
+	}
+
+	timestamp(id: number, x: DateTime): void {
+		checkBigRange(x.seconds, signedMin(64n), signedMax(64n));
+		checkRange(x.nanos, 0, 999999999);
+		if (x.seconds === 0n && x.nanos === 0) {
+			return;
+		}
+		if (x.seconds >= 0n && x.seconds < 1n << 32n) {
+			this.byte(id);
+			this.reserve(4).setUint32(0, Number(x.seconds));
+		} else {
+			this.byte(id | 0x80);
+			this.reserve(8).setBigInt64(0, x.seconds);
+		}
+		this.reserve(4).setUint32(0, x.nanos);
+	}
+
+	text(id: number, x: string): void {
+		if (x.length !== 0) {
+			this.byte(id);
+			this.textElement(x);
+		}
+	}
+
+	binary(id: number, x: Uint8Array): void {
+		if (x.length !== 0) {
+			this.byte(id);
+			this.binaryElement(x);
+		}
+	}
+
+	opaque(id: number, x: Uint8Array, size: number): void {
+		if (x.length !== size) {
+			throw new RangeError(`colfer: opaque data of ${x.length} bytes instead of ${size}`);
+		}
+		if (x.some((b) => b !== 0)) {
+			this.byte(id);
+			this.raw(x);
+		}
+	}
+
+	message(id: number, x: { encode(w: ColferWriter): void } | null): void {
+		if (x !== null) {
+			this.byte(id);
+			x.encode(this);
+		}
+	}
+
+	list<T>(id: number, xs: T[], write: (x: T) => void): void {
+		if (xs.length !== 0) {
+			this.byte(id);
+			this.varint(BigInt(xs.length));
+			xs.forEach(write);
+		}
+	}
+
+	unsignedElement(x: bigint, bits: bigint): void {
+		checkBigRange(x, 0n, (1n << bits) - 1n);
+		const view = this.reserve(Number(bits / 8n));
+		for (let i = view.byteLength - 1; i >= 0; i--, x >>= 8n) {
+			view.setUint8(i, Number(x & 0xffn));
+		}
+	}
+
+	signedElement(x: bigint, bits: bigint): void {
+		checkBigRange(x, signedMin(bits), signedMax(bits));
+		this.unsignedElement(BigInt.asUintN(Number(bits), x), bits);
+	}
+
+	float32Element(x: number): void {
+		this.reserve(4).setFloat32(0, x);
+	}
+
+	float64Element(x: number): void {
+		this.reserve(8).setFloat64(0, x);
+	}
+
+	textElement(x: string): void {
+		this.binaryElement(new TextEncoder().encode(x));
+	}
+
+	binaryElement(x: Uint8Array): void {
+		this.varint(BigInt(x.length));
+		this.raw(x);
+	}
+}
+
+/** @internal */
+export class ColferReader {
+	data: Uint8Array;
+	pos = 0;
+
+	constructor(data: Uint8Array) {
+		this.data = data;
+	}
+
+	take(n: number): DataView {
+		if (n > this.data.length - this.pos) {
+			throw new Error("colfer: data is truncated");
+		}
+		const view = new DataView(this.data.buffer, this.data.byteOffset + this.pos, n);
+		this.pos += n;
+		return view;
+	}
+
+	byte(): number {
+		return this.take(1).getUint8(0);
+	}
+
+	varint(): bigint {
+		let b = this.byte();
+		if (b < 0x80) {
+			return BigInt(b);
+		}
+		let x = BigInt(b & 0x7f);
+		for (let shift = 7n; ; shift += 7n) {
+			b = this.byte();
+			if (b < 0x80 || shift === 56n) {
+				return x | (BigInt(b) << shift);
+			}
+			x |= BigInt(b & 0x7f) << shift;
+		}
+	}
+
+	length(): number {
+		const n = this.varint();
+		if (n > BigInt(this.data.length - this.pos)) {
+			throw new Error("colfer: data is truncated");
+		}
+		return Number(n);
+	}
+
+	header(): [number, boolean] {
+		const b = this.byte();
+		return [b & 0x7f, (b & 0x80) !== 0];
+	}
+
+	uint8(): number {
+		return this.byte();
+	}
+
+	uint16(flag: boolean): number {
+		return flag ? this.byte() : this.take(2).getUint16(0);
+	}
+
+	uint32(flag: boolean): number {
+		return flag
+			? this.take(4).getUint32(0)
+			: Number(this.checkOverflow(this.varint(), 0n, (1n << 32n) - 1n));
+	}
+
+	uint64(flag: boolean): bigint {
+		return flag ? this.take(8).getBigUint64(0) : this.varint();
+	}
+
+	int(flag: boolean, bits: bigint): bigint {
+		const x = flag ? -this.varint() : this.varint();
+		return this.checkOverflow(x, signedMin(bits), signedMax(bits));
+	}
+
+	float32(): number {
+		return this.take(4).getFloat32(0);
+	}
+
+	float64(): number {
+		return this.take(8).getFloat64(0);
+	}
+
+	timestamp(flag: boolean): DateTime {
+		const seconds = flag ? this.take(8).getBigInt64(0) : BigInt(this.take(4).getUint32(0));
+		return { seconds, nanos: this.take(4).getUint32(0) };
+	}
+
+	text(): string {
+		return new TextDecoder("utf-8", { fatal: true }).decode(this.binary());
+	}
+
+	binary(): Uint8Array {
+		return this.opaque(this.length());
+	}
+
+	opaque(size: number): Uint8Array {
+		this.take(size);
+		return this.data.slice(this.pos - size, this.pos);
+	}
+
+	list<T>(read: () => T): T[] {
+		const n = this.length();
+		const xs: T[] = [];
+		for (let i = 0; i < n; i++) {
+			xs.push(read());
+		}
+		return xs;
+	}
+
+	unsignedElement(bits: bigint): bigint {
+		const view = this.take(Number(bits / 8n));
+		let x = 0n;
+		for (let i = 0; i < view.byteLength; i++) {
+			x = (x << 8n) | BigInt(view.getUint8(i));
+		}
+		return x;
+	}
+
+	signedElement(bits: bigint): bigint {
+		return BigInt.asIntN(Number(bits), this.unsignedElement(bits));
+	}
+
+	checkOverflow(x: bigint, min: bigint, max: bigint): bigint {
+		if (x < min || x > max) {
+			throw new Error("colfer: integer overflow");
+		}
+		return x;
+	}
+}
+
+export class Person {
+	name: string = "";
+	last_order: shop.Order | null = null;
+
+	/** Returns the encoding of the message. */
+	marshal(): Uint8Array {
+		const w = new ColferWriter();
+		this.encode(w);
+		return w.finish();
+	}
+
+	/** Decodes a message from the start of `data`. */
+	static unmarshal(data: Uint8Array): Person {
+		return Person.decode(new ColferReader(data));
+	}
+
+	/** @internal */
+	encode(w: ColferWriter): void {
+		w.text(0, this.name);
+		w.message(1, this.last_order);
+		w.byte(0x7f);
+	}
+
+	/** @internal */
+	static decode(r: ColferReader): Person {
+		const obj = new Person();
+		let [id, flag] = r.header();
+		if (id === 0) {
+			obj.name = r.text();
+			[id, flag] = r.header();
+		}
+		if (id === 1) {
+			obj.last_order = shop.Order.decode(r);
+			[id, flag] = r.header();
+		}
+		if (id !== 0x7f) {
+			throw new Error(`colfer: unexpected field ${id} in Person`);
+		}
+		void flag;
+		return obj;
+	}
+}
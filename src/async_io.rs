@@ -0,0 +1,69 @@
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::scan::Scanner;
+use crate::{try_decode, DecodeLimits, Error, ErrorKind, Message, Result};
+
+/// Encodes `message` to the asynchronous writer `W`.
+///
+/// The message is encoded to a buffer first, which is then written in full.
+pub async fn encode_async<W, M>(w: &mut W, message: &M) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+    M: Message,
+{
+    w.write_all(&message.to_vec()?).await?;
+    Ok(())
+}
+
+/// Decodes a message from the asynchronous reader `R`, consuming exactly the bytes of the
+/// message.
+pub async fn decode_async<M, R>(r: &mut R) -> Result<M>
+where
+    M: Message,
+    R: AsyncBufRead + Unpin,
+{
+    decode_async_with_limits(r, &DecodeLimits::default()).await
+}
+
+/// Decodes a message from the asynchronous reader `R`, failing with
+/// [`ErrorKind::LimitExceeded`] if the input exceeds `limits`.
+///
+/// Data is buffered until it holds a complete message, which is found by scanning the field
+/// headers and lengths as data arrives, picking up where the previous scan stopped. The
+/// message is only decoded once complete. The buffer never grows past `limits.max_size`.
+pub async fn decode_async_with_limits<M, R>(r: &mut R, limits: &DecodeLimits) -> Result<M>
+where
+    M: Message,
+    R: AsyncBufRead + Unpin,
+{
+    let mut buf = Vec::new();
+    let mut scanner = Scanner::default();
+    loop {
+        let available = r.fill_buf().await?;
+        if available.is_empty() {
+            return Err(Error::new(ErrorKind::Truncated).with_offset(buf.len() as u64));
+        }
+        let len = available.len();
+
+        if buf.is_empty() {
+            // Fast path, the reader buffer holds the whole message.
+            if scanner.scan::<M>(available, limits) {
+                if let Some((message, n)) = try_decode(available, limits)? {
+                    r.consume(n);
+                    return Ok(message);
+                }
+            }
+            buf.extend_from_slice(available);
+        } else {
+            let buffered = buf.len();
+            buf.extend_from_slice(available);
+            if scanner.scan::<M>(&buf, limits) {
+                if let Some((message, n)) = try_decode(&buf, limits)? {
+                    r.consume(n - buffered);
+                    return Ok(message);
+                }
+            }
+        }
+        r.consume(len);
+    }
+}
@@ -0,0 +1,218 @@
+//! Framing of colfer message streams.
+//!
+//! Messages are either delimited by their own end marker, which requires scanning the fields
+//! to find the end of a message, or prefixed with their length as a 32-bit big-endian integer.
+//! Both ways enforce [`DecodeLimits::max_size`] on every message.
+//!
+//! With the `codec` feature, [`ColferCodec`] implements the tokio-util `Encoder` and `Decoder`
+//! traits, so that messages can be sent over `Framed` transports.
+
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+use byteorder::{ByteOrder, WriteBytesExt, BE};
+
+use crate::scan::Scanner;
+use crate::{
+    from_bytes_exact, try_decode, DecodeLimits, Error, ErrorKind, LimitError, Message, Result,
+};
+
+/// Encodes and decodes a stream of messages of type `M`.
+pub struct ColferCodec<M> {
+    length_prefixed: bool,
+    limits: DecodeLimits,
+    scanner: Scanner,
+    _marker: PhantomData<fn() -> M>,
+}
+
+impl<M> Default for ColferCodec<M> {
+    fn default() -> Self {
+        Self {
+            length_prefixed: false,
+            limits: DecodeLimits::default(),
+            scanner: Scanner::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M> Clone for ColferCodec<M> {
+    fn clone(&self) -> Self {
+        Self {
+            length_prefixed: self.length_prefixed,
+            limits: self.limits,
+            scanner: Scanner::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<M> std::fmt::Debug for ColferCodec<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColferCodec")
+            .field("length_prefixed", &self.length_prefixed)
+            .field("limits", &self.limits)
+            .finish()
+    }
+}
+
+impl<M: Message> ColferCodec<M> {
+    /// Creates a codec for messages delimited by their end marker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefix each message with its length instead, which avoids decoding incomplete messages
+    /// while they are being received.
+    pub fn length_prefixed(self, enabled: bool) -> Self {
+        Self {
+            length_prefixed: enabled,
+            ..self
+        }
+    }
+
+    /// Set the limits for decoding. The size limit applies to encoding as well.
+    pub fn limits(self, limits: DecodeLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Encodes a frame holding `message` to writer `W`.
+    pub fn encode_frame<W: Write>(&self, message: &M, w: &mut W) -> Result<()> {
+        let size = message.size();
+        if size > self.limits.max_size {
+            return Err(LimitError::Size.into());
+        }
+        if self.length_prefixed {
+            w.write_u32::<BE>(u32::try_from(size).map_err(|_| LimitError::Size)?)?;
+        }
+        message.encode(w)
+    }
+
+    /// Decodes a frame from the start of `data`, returning the message along with the length
+    /// of the frame, or `None` if `data` does not hold a complete frame yet.
+    ///
+    /// The codec keeps track of how far an incomplete frame has been scanned, so after `None`
+    /// the next call must pass the same frame at the start of `data`, with more bytes appended.
+    pub fn decode_frame(&mut self, data: &[u8]) -> Result<Option<(M, usize)>> {
+        if !self.length_prefixed {
+            if !self.scanner.scan::<M>(data, &self.limits) {
+                return Ok(None);
+            }
+            let res = try_decode(data, &self.limits);
+            if !matches!(res, Ok(None)) {
+                self.scanner = Scanner::default();
+            }
+            return res;
+        }
+        if data.len() < 4 {
+            return Ok(None);
+        }
+        let size = usize::try_from(BE::read_u32(data)).unwrap_or(usize::MAX);
+        if size > self.limits.max_size {
+            return Err(LimitError::Size.into());
+        }
+        match data.get(4..4 + size) {
+            Some(frame) => Ok(Some((from_bytes_exact(frame, &self.limits)?, 4 + size))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns a blocking iterator over the messages read from `R`.
+    pub fn iter<R: Read>(self, r: R) -> FrameIter<R, M> {
+        FrameIter {
+            codec: self,
+            r,
+            buf: Vec::new(),
+            start: 0,
+            done: false,
+        }
+    }
+}
+
+/// A blocking iterator over the messages of a stream, created by [`ColferCodec::iter`].
+///
+/// The iterator ends when the stream ends between two messages, and fails with
+/// [`ErrorKind::Truncated`] if it ends within a message.
+pub struct FrameIter<R, M> {
+    codec: ColferCodec<M>,
+    r: R,
+    buf: Vec<u8>,
+    start: usize,
+    done: bool,
+}
+
+impl<R: Read, M: Message> FrameIter<R, M> {
+    fn next_frame(&mut self) -> Result<Option<M>> {
+        loop {
+            if let Some((message, n)) = self.codec.decode_frame(&self.buf[self.start..])? {
+                self.start += n;
+                return Ok(Some(message));
+            }
+
+            self.buf.drain(..self.start);
+            self.start = 0;
+            let mut chunk = [0; 8 * 1024];
+            let n = match self.r.read(&mut chunk) {
+                Ok(n) => n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            if n == 0 {
+                if self.buf.is_empty() {
+                    return Ok(None);
+                }
+                return Err(Error::new(ErrorKind::Truncated).with_offset(self.buf.len() as u64));
+            }
+            self.buf.extend_from_slice(&chunk[..n]);
+        }
+    }
+}
+
+impl<R: Read, M: Message> Iterator for FrameIter<R, M> {
+    type Item = Result<M>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_frame().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+#[cfg(feature = "codec")]
+mod tokio_codec {
+    use bytes::{Buf, BufMut, BytesMut};
+    use tokio_util::codec::{Decoder, Encoder};
+
+    use super::ColferCodec;
+    use crate::{Error, Message, Result};
+
+    impl<M: Message> Encoder<M> for ColferCodec<M> {
+        type Error = Error;
+
+        fn encode(&mut self, item: M, dst: &mut BytesMut) -> Result<()> {
+            dst.reserve(item.size() + 4);
+            self.encode_frame(&item, &mut dst.writer())
+        }
+    }
+
+    impl<M: Message> Decoder for ColferCodec<M> {
+        type Item = M;
+        type Error = Error;
+
+        fn decode(&mut self, src: &mut BytesMut) -> Result<Option<M>> {
+            match self.decode_frame(src)? {
+                Some((message, n)) => {
+                    src.advance(n);
+                    Ok(Some(message))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
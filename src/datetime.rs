@@ -1,22 +1,32 @@
 #[cfg(feature = "chrono")]
-use chrono::{DateTime as ChronoDateTime, FixedOffset, Local, TimeZone, Utc};
+use chrono::{DateTime as ChronoDateTime, Local, TimeZone, Utc};
 
 /// A datetime type.
 ///
 /// If the `chrono` feature is enabled, then `From<chrono::DateTime<TZ>>` and `Into<chrono::DateTime<TZ>>` are implemented for it.
-#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+///
+/// If the `serde` feature is enabled, then `Serialize` and `Deserialize` are implemented for it.
+#[derive(Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+// Must match `SERDE_NAME`.
+#[cfg_attr(feature = "serde", serde(rename = "$colfer::DateTime"))]
 pub struct DateTime {
-    /// Number of non-leap-milliseconds since January 1, 1970 UTC.
+    /// Number of non-leap-seconds since January 1, 1970 UTC.
     pub seconds: i64,
 
     /// Number of nanoseconds since the last second boundary.
     pub nano_seconds: u32,
 }
 
+/// The serde struct name of `DateTime`, which lets the colfer serde format encode it as a
+/// timestamp rather than a nested message.
+#[cfg(feature = "serde")]
+pub(crate) const SERDE_NAME: &str = "$colfer::DateTime";
+
 #[cfg(feature = "chrono")]
 impl From<DateTime> for ChronoDateTime<Utc> {
     fn from(t: DateTime) -> Self {
-        Utc.timestamp(t.seconds, t.nano_seconds)
+        Utc.timestamp_opt(t.seconds, t.nano_seconds).unwrap()
     }
 }
 
@@ -24,7 +34,7 @@ impl From<DateTime> for ChronoDateTime<Utc> {
 impl From<ChronoDateTime<Utc>> for DateTime {
     fn from(t: ChronoDateTime<Utc>) -> Self {
         Self {
-            seconds: t.timestamp_millis(),
+            seconds: t.timestamp(),
             nano_seconds: t.timestamp_subsec_nanos(),
         }
     }
@@ -33,7 +43,7 @@ impl From<ChronoDateTime<Utc>> for DateTime {
 #[cfg(feature = "chrono")]
 impl From<DateTime> for ChronoDateTime<Local> {
     fn from(t: DateTime) -> Self {
-        Local.timestamp(t.seconds, t.nano_seconds)
+        ChronoDateTime::<Utc>::from(t).with_timezone(&Local)
     }
 }
 
@@ -41,7 +51,7 @@ impl From<DateTime> for ChronoDateTime<Local> {
 impl From<ChronoDateTime<Local>> for DateTime {
     fn from(t: ChronoDateTime<Local>) -> Self {
         Self {
-            seconds: t.timestamp_millis(),
+            seconds: t.timestamp(),
             nano_seconds: t.timestamp_subsec_nanos(),
         }
     }
@@ -0,0 +1,726 @@
+use std::convert::TryFrom;
+use std::io::{Cursor, Read};
+
+use byteorder::ReadBytesExt;
+use serde::de::value::MapDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+
+use crate::datetime::SERDE_NAME;
+use crate::limits::SizeLimited;
+use crate::types::{read_uint, Element};
+use crate::{read_header, DateTime, DecodeLimits, Error, ErrorKind, Result, Type};
+
+/// Deserializes an instance of `T` from the colfer wire format in reader `R`.
+///
+/// `T` must be a struct, whose fields are matched to the field indexes of the message in
+/// declaration order. Absent fields take their zero value, `Option::None` included.
+pub fn from_reader<R: Read, T: DeserializeOwned>(r: &mut R) -> Result<T> {
+    from_reader_with_limits(r, &DecodeLimits::default())
+}
+
+/// Deserializes an instance of `T` from reader `R`, failing with
+/// [`ErrorKind::LimitExceeded`] if the input exceeds `limits`.
+pub fn from_reader_with_limits<R: Read, T: DeserializeOwned>(
+    r: &mut R,
+    limits: &DecodeLimits,
+) -> Result<T> {
+    let mut r = SizeLimited::new(r, limits.max_size);
+    T::deserialize(Deserializer {
+        r: &mut r,
+        limits: *limits,
+    })
+    .map_err(|err| err.with_offset(r.position()))
+}
+
+/// Deserializes an instance of `T` from `data`, failing if any bytes follow it.
+pub fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T> {
+    from_slice_with_limits(data, &DecodeLimits::default())
+}
+
+/// Deserializes an instance of `T` from `data`, failing with [`ErrorKind::LimitExceeded`] if
+/// the input exceeds `limits`.
+pub fn from_slice_with_limits<T: DeserializeOwned>(
+    data: &[u8],
+    limits: &DecodeLimits,
+) -> Result<T> {
+    let mut r = Cursor::new(data);
+    let value = from_reader_with_limits(&mut r, limits)?;
+    if r.position() != data.len() as u64 {
+        return Err(Error::new(ErrorKind::TrailingData).with_offset(r.position()));
+    }
+    Ok(value)
+}
+
+#[inline]
+fn unsupported<T>(what: &'static str) -> Result<T> {
+    Err(ErrorKind::Unsupported(what).into())
+}
+
+/// Deserializes a top-level message.
+struct Deserializer<'r, R> {
+    r: &'r mut R,
+    limits: DecodeLimits,
+}
+
+impl<'de, 'r, R: Read> de::Deserializer<'de> for Deserializer<'r, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        unsupported("a top-level value other than a struct")
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_map(StructAccess::new(self.r, name, fields, self.limits))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Reads the fields of a message in order, up to and including the end marker.
+struct StructAccess<'r, R> {
+    r: &'r mut R,
+    name: &'static str,
+    fields: &'static [&'static str],
+    limits: DecodeLimits,
+    index: usize,
+    header: Option<(u8, bool)>,
+    ended: bool,
+}
+
+impl<'r, R: Read> StructAccess<'r, R> {
+    fn new(
+        r: &'r mut R,
+        name: &'static str,
+        fields: &'static [&'static str],
+        limits: DecodeLimits,
+    ) -> Self {
+        Self {
+            r,
+            name,
+            fields,
+            limits,
+            index: 0,
+            header: None,
+            ended: false,
+        }
+    }
+
+    fn next_header(&mut self) -> Result<()> {
+        let (id, flag) = read_header(self.r)?;
+        if id == 0x7f {
+            self.ended = true;
+        } else if (id as usize) < self.index {
+            return Err(ErrorKind::UnexpectedField(id).into());
+        } else if id as usize >= self.fields.len() {
+            return Err(ErrorKind::UnknownField(id).into());
+        } else {
+            self.header = Some((id, flag));
+        }
+        Ok(())
+    }
+}
+
+impl<'de, 'r, R: Read> MapAccess<'de> for StructAccess<'r, R> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if !self.ended && self.header.is_none() {
+            self.next_header()
+                .map_err(|err| err.with_struct(self.name))?;
+        }
+        match self.fields.get(self.index) {
+            Some(field) => seed.deserialize((*field).into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let index = self.index;
+        self.index += 1;
+        let value = match self.header {
+            Some((id, flag)) if id as usize == index => {
+                self.header = None;
+                seed.deserialize(FieldDeserializer {
+                    r: &mut *self.r,
+                    flag,
+                    limits: self.limits,
+                })
+            }
+            _ => seed.deserialize(Absent),
+        };
+        value.map_err(|err| err.with_field(self.name, self.fields[index]))
+    }
+}
+
+/// Deserializes the value of a field that is present in the input.
+struct FieldDeserializer<'r, R> {
+    r: &'r mut R,
+    flag: bool,
+    limits: DecodeLimits,
+}
+
+impl<'r, R: Read> FieldDeserializer<'r, R> {
+    fn decode<T: Type>(self) -> Result<T> {
+        T::decode(self.r, self.flag, &self.limits)
+    }
+}
+
+impl<'de, 'r, R: Read> de::Deserializer<'de> for FieldDeserializer<'r, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        unsupported("a field of this type")
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(self.decode()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let v = i8::try_from(self.decode::<i32>()?).map_err(|_| ErrorKind::IntegerOverflow)?;
+        visitor.visit_i8(v)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let v = i16::try_from(self.decode::<i32>()?).map_err(|_| ErrorKind::IntegerOverflow)?;
+        visitor.visit_i16(v)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(self.decode()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(self.decode()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.decode()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(self.decode()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(self.decode()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(self.decode()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.decode()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.decode()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.decode()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.decode()?)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Binaries may be longer than `max_list_size`, so the element type checks the list size.
+        let len = self.limits.check_size(read_uint(self.r)?)?;
+        visitor.visit_seq(ListAccess {
+            r: self.r,
+            limits: self.limits,
+            index: 0,
+            len,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == SERDE_NAME {
+            let t: DateTime = self.decode()?;
+            return visit_datetime(t, visitor);
+        }
+        let limits = self.limits.nested()?;
+        visitor.visit_map(StructAccess::new(self.r, name, fields, limits))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let index: u32 = self.decode()?;
+        visitor.visit_enum(index.into_deserializer())
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        let mut data = vec![0; len];
+        self.r.read_exact(&mut data)?;
+        visitor.visit_seq(de::value::SeqDeserializer::new(data.into_iter()))
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 tuple_struct map identifier ignored_any
+    }
+}
+
+fn visit_datetime<'de, V: Visitor<'de>>(t: DateTime, visitor: V) -> Result<V::Value> {
+    let fields = vec![
+        ("seconds", t.seconds),
+        ("nano_seconds", t.nano_seconds as i64),
+    ];
+    visitor.visit_map(MapDeserializer::new(fields.into_iter()))
+}
+
+/// Reads the elements of a list.
+struct ListAccess<'r, R> {
+    r: &'r mut R,
+    limits: DecodeLimits,
+    index: usize,
+    len: usize,
+}
+
+impl<'de, 'r, R: Read> SeqAccess<'de> for ListAccess<'r, R> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.index == self.len {
+            return Ok(None);
+        }
+        self.index += 1;
+        seed.deserialize(ElementDeserializer {
+            r: &mut *self.r,
+            limits: self.limits,
+            index: self.index - 1,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/// Deserializes a list element.
+///
+/// A list of `u8` is decoded from binary, so that `Vec<u8>` fields match the generated code.
+struct ElementDeserializer<'r, R> {
+    r: &'r mut R,
+    limits: DecodeLimits,
+    index: usize,
+}
+
+impl<'r, R: Read> ElementDeserializer<'r, R> {
+    fn decode<T: Type>(self) -> Result<T> {
+        self.limits.check_list_size(self.index as u64 + 1)?;
+        T::decode(self.r, false, &self.limits)
+    }
+
+    fn integer<T: Element>(self) -> Result<T> {
+        self.limits.check_list_size(self.index as u64 + 1)?;
+        T::read_element(self.r, &self.limits)
+    }
+}
+
+impl<'de, 'r, R: Read> de::Deserializer<'de> for ElementDeserializer<'r, R> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        unsupported("a list of this type")
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(self.integer()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i16(self.integer()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(self.integer()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(self.integer()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.r.read_u8()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(self.integer()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(self.integer()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(self.integer()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(self.decode()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(self.decode()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.decode()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(self.decode()?)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let data: Vec<u8> = self.decode()?;
+        visitor.visit_seq(de::value::SeqDeserializer::new(data.into_iter()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.limits.check_list_size(self.index as u64 + 1)?;
+        let limits = self.limits.nested()?;
+        visitor.visit_map(StructAccess::new(self.r, name, fields, limits))
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 option unit unit_struct tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Deserializes the zero value of a field that is absent from the input.
+struct Absent;
+
+impl<'de> de::Deserializer<'de> for Absent {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        unsupported("a field of this type")
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(false)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(0)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i16(0)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(0)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(0)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(0)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(0)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(0)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(0)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(0.0)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(0.0)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(String::new())
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bytes(&[])
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_byte_buf(Vec::new())
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(std::iter::empty::<u8>()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        if name == SERDE_NAME {
+            return visit_datetime(DateTime::default(), visitor);
+        }
+        visitor.visit_map(MapDeserializer::new(
+            fields.iter().map(|field| (*field, Absent)),
+        ))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(0u32.into_deserializer())
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(de::value::SeqDeserializer::new((0..len).map(|_| 0u8)))
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 tuple_struct map identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Absent {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::*;
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    enum Kind {
+        #[default]
+        A,
+        B,
+    }
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    struct Inner {
+        i8: i8,
+        c: char,
+        kind: Kind,
+    }
+
+    #[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+    struct Outer {
+        b: bool,
+        u16: u16,
+        i64: i64,
+        f64: f64,
+        t: DateTime,
+        s: String,
+        a: Vec<u8>,
+        inner: Inner,
+        o: Option<Box<Outer>>,
+        os: Vec<Inner>,
+        ss: Vec<String>,
+        r#as: Vec<Vec<u8>>,
+        f32s: Vec<f32>,
+        i32s: Vec<i32>,
+        u64s: Vec<u64>,
+        opaque: [u8; 4],
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let value = Outer {
+            b: true,
+            u16: 300,
+            i64: -7,
+            f64: 1.5,
+            t: DateTime {
+                seconds: 1 << 40,
+                nano_seconds: 1,
+            },
+            s: "hello".to_string(),
+            a: vec![1, 2, 3],
+            inner: Inner {
+                i8: -1,
+                c: 'x',
+                kind: Kind::B,
+            },
+            o: Some(Box::new(Outer {
+                u16: 1,
+                ..Outer::default()
+            })),
+            os: vec![Inner::default(), Inner::default()],
+            ss: vec!["a".to_string(), String::new()],
+            r#as: vec![vec![4], vec![5, 6]],
+            f32s: vec![-2.0],
+            i32s: vec![-1, 0, i32::MAX],
+            u64s: vec![u64::MAX],
+            opaque: [0, 0, 0, 1],
+        };
+        let data = to_vec(&value).unwrap();
+        assert_eq!(from_slice::<Outer>(&data).unwrap(), value);
+    }
+
+    #[test]
+    fn test_zero_values() {
+        #[derive(Serialize)]
+        struct Empty {}
+
+        // The nested `inner` message is always encoded, like a present optional field.
+        assert_eq!(to_vec(&Outer::default()).unwrap(), [7, 0x7f, 0x7f]);
+        assert_eq!(from_slice::<Outer>(&[0x7f]).unwrap(), Outer::default());
+        assert_eq!(to_vec(&Empty {}).unwrap(), [0x7f]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = from_slice::<Inner>(&[3, 0x7f]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::UnknownField(3)));
+        assert_eq!(err.struct_name(), Some("Inner"));
+
+        let err = from_slice::<Inner>(&[0x7f, 0]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::TrailingData));
+
+        let err = from_slice::<Inner>(&[0, 1]).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::MissingEnd));
+
+        let err = to_vec(&1u32).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::Utf8Error;
+
+use crate::LimitError;
+
+/// A specialized `Result` type for colfer operations.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The kind of an [`Error`].
+#[derive(Debug)]
+pub enum ErrorKind {
+    /// An I/O error of the underlying reader or writer.
+    Io(std::io::Error),
+
+    /// The input ended before the message was complete.
+    Truncated,
+
+    /// A text field is not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+
+    /// The input exceeds one of the [`DecodeLimits`](crate::DecodeLimits).
+    LimitExceeded(LimitError),
+
+    /// The input contains a field id that is not defined by the schema.
+    UnknownField(u8),
+
+    /// The input contains a field id out of order, or more than once.
+    UnexpectedField(u8),
+
+    /// The message is not terminated by an end marker.
+    MissingEnd,
+
+    /// An integer does not fit in the type of its field.
+    IntegerOverflow,
+
+    /// The input continues after the end of the message.
+    TrailingData,
+
+    /// A serde type that has no representation in colfer.
+    Unsupported(&'static str),
+
+    /// A custom error raised by a serde `Serialize` or `Deserialize` implementation.
+    Custom(String),
+
+    /// A message whose schema is only known at runtime does not match it, such as a field
+    /// holding a value of another type.
+    SchemaMismatch(String),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Io(err) => write!(f, "io error: {}", err),
+            ErrorKind::Truncated => f.write_str("unexpected end of input"),
+            ErrorKind::InvalidUtf8(err) => write!(f, "invalid UTF-8: {}", err),
+            ErrorKind::LimitExceeded(err) => write!(f, "{}", err),
+            ErrorKind::UnknownField(id) => write!(f, "unknown field id {}", id),
+            ErrorKind::UnexpectedField(id) => write!(f, "unexpected field id {}", id),
+            ErrorKind::MissingEnd => f.write_str("missing end marker"),
+            ErrorKind::IntegerOverflow => f.write_str("integer out of range"),
+            ErrorKind::TrailingData => f.write_str("trailing data after the message"),
+            ErrorKind::Unsupported(what) => write!(f, "{} is not supported", what),
+            ErrorKind::Custom(msg) => f.write_str(msg),
+            ErrorKind::SchemaMismatch(msg) => f.write_str(msg),
+        }
+    }
+}
+
+/// The error type for encoding and decoding colfer messages.
+///
+/// Besides its [`ErrorKind`], a decoding error carries the struct and field being decoded and
+/// the byte offset in the input where the error occurred, if known.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    struct_name: Option<&'static str>,
+    field_name: Option<&'static str>,
+    offset: Option<u64>,
+}
+
+impl Error {
+    /// Creates an error of the specified kind.
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            struct_name: None,
+            field_name: None,
+            offset: None,
+        }
+    }
+
+    /// Returns the kind of this error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the name of the struct that was being decoded, if known.
+    pub fn struct_name(&self) -> Option<&'static str> {
+        self.struct_name
+    }
+
+    /// Returns the name of the field that was being decoded, if known.
+    pub fn field_name(&self) -> Option<&'static str> {
+        self.field_name
+    }
+
+    /// Returns the byte offset in the input where the error occurred, if known.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// Attaches the struct and field being decoded, unless a nested message already did.
+    #[doc(hidden)]
+    pub fn with_field(mut self, struct_name: &'static str, field_name: &'static str) -> Self {
+        if self.struct_name.is_none() {
+            self.struct_name = Some(struct_name);
+            self.field_name = Some(field_name);
+        }
+        self
+    }
+
+    /// Attaches the struct being decoded, unless a nested message already did.
+    #[doc(hidden)]
+    pub fn with_struct(mut self, struct_name: &'static str) -> Self {
+        if self.struct_name.is_none() {
+            self.struct_name = Some(struct_name);
+        }
+        self
+    }
+
+    /// Attaches the byte offset in the input, unless it is already known.
+    #[doc(hidden)]
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        if self.offset.is_none() {
+            self.offset = Some(offset);
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "colfer: {}", self.kind)?;
+        match (self.struct_name, self.field_name) {
+            (Some(struct_name), Some(field_name)) => {
+                write!(f, " in field `{}.{}`", struct_name, field_name)?
+            }
+            (Some(struct_name), None) => write!(f, " in struct `{}`", struct_name)?,
+            _ => {}
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {}", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Io(err) => Some(err),
+            ErrorKind::InvalidUtf8(err) => Some(err),
+            ErrorKind::LimitExceeded(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::new(kind)
+    }
+}
+
+impl From<LimitError> for Error {
+    fn from(err: LimitError) -> Self {
+        Error::new(ErrorKind::LimitExceeded(err))
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(err: Utf8Error) -> Self {
+        Error::new(ErrorKind::InvalidUtf8(err))
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::UnexpectedEof {
+            return Error::new(ErrorKind::Truncated);
+        }
+        if let Some(limit) = err
+            .get_ref()
+            .and_then(|err| err.downcast_ref::<LimitError>())
+        {
+            return Error::new(ErrorKind::LimitExceeded(*limit));
+        }
+        Error::new(ErrorKind::Io(err))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new(ErrorKind::Custom(msg.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::new(ErrorKind::Custom(msg.to_string()))
+    }
+}
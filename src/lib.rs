@@ -1,17 +1,57 @@
 //! `Colfer` is a binary serialization format optimized for speed and size, this crate
 //! is a Rust implementation of the [colfer](https://github.com/pascaldekloe/colfer).
+//!
+//! The schema types `int8`, `int16`, `opaque8` to `opaque64` and lists of integers are
+//! extensions of colfer-rs, which the colfer format does not define. Their encodings follow
+//! the closest rules of the format, but other colfer implementations can not read them, so
+//! schemas that use them are not interoperable.
 
 #![warn(missing_docs)]
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "tokio")]
+mod async_io;
+pub mod codec;
 mod datetime;
+#[cfg(feature = "serde")]
+mod de;
+mod error;
+mod limits;
+mod mapped;
+mod scan;
+#[cfg(feature = "serde")]
+mod ser;
 mod types;
+mod view;
 
-use std::io::{Cursor, Read, Result, Write};
+use std::io::{Cursor, Read, Write};
 
+#[cfg(feature = "tokio")]
+pub use async_io::{decode_async, decode_async_with_limits, encode_async};
 use byteorder::{ReadBytesExt, WriteBytesExt};
 pub use datetime::DateTime;
-pub use types::{decode_message, decode_messages, encode_message, encode_messages, Type};
+#[cfg(feature = "serde")]
+pub use de::{from_reader, from_reader_with_limits, from_slice, from_slice_with_limits};
+pub use error::{Error, ErrorKind, Result};
+use limits::SizeLimited;
+pub use limits::{DecodeLimits, LimitError};
+pub use scan::Shape;
+#[cfg(feature = "serde")]
+pub use ser::{to_vec, to_writer};
+pub use types::{
+    decode_list_len, decode_message, decode_messages, encode_list_header, encode_message,
+    encode_messages, message_size, messages_size, Element, Type,
+};
+pub use view::{
+    decode_message_view, view_from_bytes_exact, ElementView, ListView, ListViewIter, MessageView,
+    TypeView,
+};
+
+#[cfg(feature = "derive")]
+pub use colfer_derive::Message;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde;
 
 /// `MAX_SIZE` is the upper limit for serial byte sizes.
 pub const MAX_SIZE: usize = 16 * 1024 * 1024;
@@ -19,17 +59,66 @@ pub const MAX_SIZE: usize = 16 * 1024 * 1024;
 /// `MAX_LIST_SIZE` is the upper limit for the number of elements in a list.
 pub const MAX_LIST_SIZE: usize = 64 * 1024;
 
+/// `MAX_DEPTH` is the upper limit for the nesting depth of messages.
+pub const MAX_DEPTH: usize = 100;
+
+/// Includes the code generated by `colfer-build` in `OUT_DIR`.
+///
+/// Without arguments, includes the modules of all packages, which requires the include file of
+/// `colfer_build::Config::include_file`. With the name of a package, includes the code of that
+/// package in the current module, which must be next to the modules of the packages it
+/// references.
+///
+/// ```ignore
+/// mod schema {
+///     colfer::include_schema!();
+/// }
+///
+/// let order = schema::shop::Order::default();
+/// ```
+#[macro_export]
+macro_rules! include_schema {
+    () => {
+        include!(concat!(env!("OUT_DIR"), "/_colfer_includes.rs"));
+    };
+    ($package:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $package, ".rs"));
+    };
+}
+
 /// A colfer message.
+///
+/// Implementations are generated from schemas by `colfer-build`, or derived with
+/// `#[derive(colfer::Message)]` if the `derive` feature is enabled.
 pub trait Message: Sized {
     /// Encodes the message to writer `W`.
     fn encode<W: Write>(&self, w: &mut W) -> Result<()>;
 
     /// Decodes an instance of the message from reader `R`.
-    fn decode<R: Read>(r: &mut R) -> Result<Self>;
+    fn decode<R: Read>(r: &mut R) -> Result<Self> {
+        Self::decode_with_limits(r, &DecodeLimits::default())
+    }
+
+    /// Decodes an instance of the message from reader `R`, failing with
+    /// [`ErrorKind::LimitExceeded`] if the input exceeds `limits`.
+    fn decode_with_limits<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        let mut r = SizeLimited::new(r, limits.max_size);
+        Self::decode_fields(&mut r, limits).map_err(|err| err.with_offset(r.position()))
+    }
+
+    /// Decodes the fields of the message, without enforcing the total serial size.
+    #[doc(hidden)]
+    fn decode_fields<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self>;
 
     /// Returns the encoded length of the message.
     fn size(&self) -> usize;
 
+    /// Returns the shapes of the fields by index, or `None` if they are not known.
+    #[doc(hidden)]
+    fn shapes() -> Option<&'static [Shape]> {
+        None
+    }
+
     /// Encodes the message to `Vec<u8>`.
     fn to_vec(&self) -> Result<Vec<u8>> {
         let mut data = Vec::new();
@@ -39,19 +128,96 @@ pub trait Message: Sized {
 
     /// Decodes an instance of the message from `Vec<u8>`.
     fn from_bytes(data: &[u8]) -> Result<Self> {
-        Self::decode(&mut Cursor::new(data))
+        Self::from_bytes_with_limits(data, &DecodeLimits::default())
+    }
+
+    /// Decodes an instance of the message from `Vec<u8>`, failing with
+    /// [`ErrorKind::LimitExceeded`] if the input exceeds `limits`.
+    fn from_bytes_with_limits(data: &[u8], limits: &DecodeLimits) -> Result<Self> {
+        Self::decode_with_limits(&mut Cursor::new(data), limits)
+    }
+}
+
+/// Decodes a message from the start of `data`, returning it along with the number of bytes it
+/// occupies, or `None` if `data` ends before the message does.
+///
+/// This lets a buffered reader find out whether it has received a complete message.
+pub fn try_decode<M: Message>(data: &[u8], limits: &DecodeLimits) -> Result<Option<(M, usize)>> {
+    let mut r = Cursor::new(data);
+    match M::decode_with_limits(&mut r, limits) {
+        Ok(message) => Ok(Some((message, r.position() as usize))),
+        Err(err) if matches!(err.kind(), ErrorKind::Truncated | ErrorKind::MissingEnd) => Ok(None),
+        Err(err) => Err(err),
     }
 }
 
 #[inline]
 #[doc(hidden)]
 pub fn read_header<R: Read>(r: &mut R) -> Result<(u8, bool)> {
-    let d = r.read_u8()?;
+    let d = r.read_u8().map_err(|err| match err.kind() {
+        std::io::ErrorKind::UnexpectedEof => ErrorKind::MissingEnd.into(),
+        _ => Error::from(err),
+    })?;
     Ok((d & 0x7f, d & 0x80 > 0))
 }
 
+/// Checks that the header following the last known field is the end marker.
+#[inline]
+#[doc(hidden)]
+pub fn check_end(id: u8, field_count: u8) -> Result<()> {
+    if id == 0x7f {
+        Ok(())
+    } else if id < field_count {
+        Err(ErrorKind::UnexpectedField(id).into())
+    } else {
+        Err(ErrorKind::UnknownField(id).into())
+    }
+}
+
+/// Decodes a message from `data` with `decode_known`, which stops at the first unknown field id
+/// and returns it. Because colfer fields are ordered and do not carry their length, unknown
+/// fields can only be skipped at the end of a top-level message, where everything up to the
+/// final end marker belongs to them. Returns the message and the raw unknown fields.
+#[inline]
+#[doc(hidden)]
+pub fn from_bytes_unknown<'a, M, F>(
+    data: &'a [u8],
+    limits: &DecodeLimits,
+    decode_known: F,
+) -> Result<(M, &'a [u8])>
+where
+    F: FnOnce(&mut Cursor<&'a [u8]>, &DecodeLimits) -> Result<(M, u8)>,
+{
+    if data.len() > limits.max_size {
+        return Err(LimitError::Size.into());
+    }
+    let mut r = Cursor::new(data);
+    let (message, id) =
+        decode_known(&mut r, limits).map_err(|err| err.with_offset(r.position()))?;
+    let pos = r.position() as usize;
+    if id == 0x7f {
+        return Ok((message, &[]));
+    }
+    match data.last() {
+        Some(0x7f) if data.len() > pos => Ok((message, &data[pos - 1..data.len() - 1])),
+        _ => Err(Error::new(ErrorKind::MissingEnd).with_offset(data.len() as u64)),
+    }
+}
+
+/// Decodes a message from `data`, failing if any bytes follow it.
+#[inline]
+#[doc(hidden)]
+pub fn from_bytes_exact<M: Message>(data: &[u8], limits: &DecodeLimits) -> Result<M> {
+    let mut r = Cursor::new(data);
+    let message = M::decode_with_limits(&mut r, limits)?;
+    if r.position() != data.len() as u64 {
+        return Err(Error::new(ErrorKind::TrailingData).with_offset(r.position()));
+    }
+    Ok(message)
+}
+
 #[inline]
 #[doc(hidden)]
 pub fn write_end<W: Write>(w: &mut W) -> Result<()> {
-    w.write_u8(0x7f)
+    Ok(w.write_u8(0x7f)?)
 }
@@ -0,0 +1,130 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read};
+
+use crate::{Result, MAX_DEPTH, MAX_LIST_SIZE, MAX_SIZE};
+
+/// Limits enforced while decoding, to protect against malicious input.
+///
+/// The default limits are [`MAX_SIZE`], [`MAX_LIST_SIZE`] and [`MAX_DEPTH`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct DecodeLimits {
+    /// The upper limit for serial byte sizes.
+    pub max_size: usize,
+
+    /// The upper limit for the number of elements in a list.
+    pub max_list_size: usize,
+
+    /// The upper limit for the nesting depth of messages.
+    pub max_depth: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_size: MAX_SIZE,
+            max_list_size: MAX_LIST_SIZE,
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
+
+impl DecodeLimits {
+    /// Returns the limits for a message nested one level deeper.
+    #[doc(hidden)]
+    #[inline]
+    pub fn nested(&self) -> Result<Self> {
+        if self.max_depth == 0 {
+            return Err(LimitError::Depth.into());
+        }
+        Ok(Self {
+            max_depth: self.max_depth - 1,
+            ..*self
+        })
+    }
+
+    #[inline]
+    pub(crate) fn check_size(&self, size: u64) -> Result<usize> {
+        if size > self.max_size as u64 {
+            return Err(LimitError::Size.into());
+        }
+        Ok(size as usize)
+    }
+
+    #[inline]
+    pub(crate) fn check_list_size(&self, size: u64) -> Result<usize> {
+        if size > self.max_list_size as u64 {
+            return Err(LimitError::ListSize.into());
+        }
+        Ok(size as usize)
+    }
+}
+
+/// The limit that was exceeded by an
+/// [`ErrorKind::LimitExceeded`](crate::ErrorKind::LimitExceeded) error.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LimitError {
+    /// A serial byte size exceeds [`DecodeLimits::max_size`].
+    Size,
+
+    /// A list exceeds [`DecodeLimits::max_list_size`].
+    ListSize,
+
+    /// Messages are nested deeper than [`DecodeLimits::max_depth`].
+    Depth,
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::Size => f.write_str("serial size exceeds the maximum"),
+            LimitError::ListSize => f.write_str("list size exceeds the maximum"),
+            LimitError::Depth => f.write_str("nesting depth exceeds the maximum"),
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl From<LimitError> for io::Error {
+    fn from(err: LimitError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// A reader that fails with [`LimitError::Size`] once more than `remaining` bytes are read.
+pub(crate) struct SizeLimited<'a, R> {
+    inner: &'a mut R,
+    remaining: usize,
+    position: u64,
+}
+
+impl<'a, R: Read> SizeLimited<'a, R> {
+    pub(crate) fn new(inner: &'a mut R, max_size: usize) -> Self {
+        Self {
+            inner,
+            remaining: max_size,
+            position: 0,
+        }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<'a, R: Read> Read for SizeLimited<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            return Err(LimitError::Size.into());
+        }
+        let max = buf.len().min(self.remaining);
+        let n = self.inner.read(&mut buf[..max])?;
+        self.remaining -= n;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
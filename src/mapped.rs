@@ -0,0 +1,250 @@
+//! `Type` implementations for the Rust types that generated fields can be mapped to, besides
+//! the default ones.
+
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use crate::types::{element_bytes_size, write_bytes, write_element_bytes};
+use crate::{DecodeLimits, Element, Result, Shape, Type};
+
+impl Type for Arc<str> {
+    const SHAPE: Shape = Shape::Bytes;
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        write_bytes(w, id, self.as_bytes())
+    }
+
+    #[inline]
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        String::decode(r, flag, limits).map(Arc::from)
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        bytes_size(self.as_bytes())
+    }
+}
+
+impl Element for Arc<str> {
+    const LIST_SHAPE: Shape = Shape::BytesList;
+
+    #[inline]
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_element_bytes(w, self.as_bytes())
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        String::read_element(r, limits).map(Arc::from)
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        element_bytes_size(self.as_bytes())
+    }
+}
+
+#[inline]
+fn bytes_size(data: &[u8]) -> usize {
+    if !data.is_empty() {
+        1 + element_bytes_size(data)
+    } else {
+        0
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Type for bytes::Bytes {
+    const SHAPE: Shape = Shape::Bytes;
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        write_bytes(w, id, self)
+    }
+
+    #[inline]
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        Vec::<u8>::decode(r, flag, limits).map(bytes::Bytes::from)
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        bytes_size(self)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Element for bytes::Bytes {
+    const LIST_SHAPE: Shape = Shape::BytesList;
+
+    #[inline]
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_element_bytes(w, self)
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        Vec::<u8>::read_element(r, limits).map(bytes::Bytes::from)
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        element_bytes_size(self)
+    }
+}
+
+#[cfg(feature = "smol_str")]
+impl Type for smol_str::SmolStr {
+    const SHAPE: Shape = Shape::Bytes;
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        write_bytes(w, id, self.as_bytes())
+    }
+
+    #[inline]
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        String::decode(r, flag, limits).map(smol_str::SmolStr::from)
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        bytes_size(self.as_bytes())
+    }
+}
+
+#[cfg(feature = "smol_str")]
+impl Element for smol_str::SmolStr {
+    const LIST_SHAPE: Shape = Shape::BytesList;
+
+    #[inline]
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_element_bytes(w, self.as_bytes())
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        String::read_element(r, limits).map(smol_str::SmolStr::from)
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        element_bytes_size(self.as_bytes())
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Type for smallvec::SmallVec<A>
+where
+    A::Item: Element,
+{
+    const SHAPE: Shape = A::Item::LIST_SHAPE;
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        crate::types::encode_list(w, id, self)
+    }
+
+    #[inline]
+    fn decode<R: Read>(r: &mut R, _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        let l = crate::decode_list_len(r, limits)?;
+        let mut s = smallvec::SmallVec::with_capacity(l.min(crate::types::MAX_LIST_PREALLOCATION));
+        for _ in 0..l {
+            s.push(A::Item::read_element(r, limits)?);
+        }
+        Ok(s)
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        crate::types::list_size(self)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Type for chrono::DateTime<chrono::Utc> {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 8,
+        flagged: 12,
+    };
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        crate::DateTime::from(*self).encode(w, id)
+    }
+
+    #[inline]
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        use chrono::TimeZone;
+
+        let t = crate::DateTime::decode(r, flag, limits)?;
+        chrono::Utc
+            .timestamp_opt(t.seconds, t.nano_seconds)
+            .single()
+            .ok_or_else(|| crate::ErrorKind::IntegerOverflow.into())
+    }
+
+    #[inline]
+    fn size(&self) -> usize {
+        crate::DateTime::from(*self).size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+    use std::io::Cursor;
+
+    use crate::*;
+
+    fn roundtrip<T: Type + PartialEq + Debug>(value: T) {
+        let mut data = Vec::new();
+        value.encode(&mut data, 3).unwrap();
+        assert_eq!(value.size(), data.len());
+        let mut r = Cursor::new(&data);
+        let (id, flag) = read_header(&mut r).unwrap();
+        assert_eq!(id, 3);
+        assert_eq!(
+            T::decode(&mut r, flag, &DecodeLimits::default()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_arc_str() {
+        let text: std::sync::Arc<str> = "hello".into();
+        roundtrip(text.clone());
+        roundtrip(vec![text, "".into()]);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes() {
+        roundtrip(bytes::Bytes::from_static(b"\x01\x02"));
+        roundtrip(vec![bytes::Bytes::from_static(b"\x03")]);
+    }
+
+    #[cfg(feature = "smol_str")]
+    #[test]
+    fn test_smol_str() {
+        roundtrip(smol_str::SmolStr::new("hello"));
+    }
+
+    #[cfg(feature = "smallvec")]
+    #[test]
+    fn test_smallvec() {
+        roundtrip(smallvec::SmallVec::<[i32; 2]>::from_slice(&[-1, 2, 3]));
+        roundtrip(smallvec::SmallVec::<[String; 1]>::from_vec(
+            vec!["a".into()],
+        ));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono() {
+        use chrono::TimeZone;
+
+        roundtrip(chrono::Utc.timestamp_opt(1_600_000_000, 5).unwrap());
+    }
+}
@@ -0,0 +1,284 @@
+//! Finding the end of a serial as it arrives, without decoding it.
+
+use std::convert::TryFrom;
+
+use crate::{DecodeLimits, Message};
+
+/// The layout of the bytes that follow the header of a field, which lets [`Scanner`] skip the
+/// field without decoding it.
+#[doc(hidden)]
+#[derive(Clone, Copy, Debug)]
+pub enum Shape {
+    /// Nothing follows the header.
+    Empty,
+    /// `size` bytes follow the header, or `flagged` bytes if its flag is set.
+    Fixed { size: usize, flagged: usize },
+    /// A varint follows the header, or `flagged` bytes if its flag is set and `flagged` is
+    /// not `None`.
+    Varint { flagged: Option<usize> },
+    /// A varint size, followed by as many bytes.
+    Bytes,
+    /// A varint element count, followed by elements of `size` bytes.
+    FixedList { size: usize },
+    /// A varint element count, followed by elements of a varint size and as many bytes.
+    BytesList,
+    /// A nested message, with the shapes of its fields.
+    Message(fn() -> Option<&'static [Shape]>),
+    /// A varint message count, followed by the messages.
+    Messages(fn() -> Option<&'static [Shape]>),
+    /// The layout is not known, so the message has to be decoded to find its end.
+    Unknown,
+}
+
+enum Frame {
+    /// Within a message, before the header of a field or the end marker.
+    Message(&'static [Shape]),
+    /// Within a list of messages, before the next one.
+    Messages {
+        shapes: fn() -> Option<&'static [Shape]>,
+        remaining: usize,
+    },
+    /// Within a list of binaries, before the next one.
+    BytesList { remaining: usize },
+}
+
+/// Scans a serial as it arrives, to find out when it is complete. The progress is kept at
+/// the last complete field or list element, so that bytes are scanned about once no matter
+/// how the serial is split, unlike decoding it again every time more bytes arrive.
+#[derive(Default)]
+pub(crate) struct Scanner {
+    started: bool,
+    ready: bool,
+    stack: Vec<Frame>,
+    pos: usize,
+}
+
+/// Reads a varint like `read_uint` does, returning it along with its size, or `None` if
+/// `data` ends before it does.
+fn varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut x = 0;
+    for (i, &b) in data.iter().enumerate().take(9) {
+        if i == 8 {
+            return Some((x | ((b as u64) << 56), 9));
+        }
+        x |= ((b & 0x7f) as u64) << (7 * i);
+        if b < 0x80 {
+            return Some((x, i + 1));
+        }
+    }
+    None
+}
+
+impl Scanner {
+    /// Scans `data`, which holds the start of a serial of `M`, along with the bytes appended
+    /// since the last call. Returns `true` once decoding `data` no longer fails for lack of
+    /// bytes: the serial is complete, or it is invalid in a way that decoding reports.
+    ///
+    /// Messages with a field of unknown shape are always reported as ready, so that they are
+    /// decoded every time more bytes arrive.
+    pub(crate) fn scan<M: Message>(&mut self, data: &[u8], limits: &DecodeLimits) -> bool {
+        if !self.started {
+            self.started = true;
+            self.push(M::shapes(), limits);
+        }
+        while !self.ready {
+            if self.step(data, limits).is_none() {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn push(&mut self, shapes: Option<&'static [Shape]>, limits: &DecodeLimits) {
+        match shapes {
+            Some(shapes) if self.stack.len() < limits.max_depth => {
+                self.stack.push(Frame::Message(shapes))
+            }
+            // The depth limit is reported by decoding.
+            _ => self.ready = true,
+        }
+    }
+
+    /// Reads the length of a list or binary at `pos`, marking the scanner ready if it is
+    /// beyond `max`.
+    fn len(&mut self, data: &[u8], pos: usize, max: usize) -> Option<(usize, usize)> {
+        let (len, n) = varint(data.get(pos..)?)?;
+        match usize::try_from(len) {
+            Ok(len) if len <= max => Some((len, pos + n)),
+            _ => {
+                self.ready = true;
+                Some((0, pos + n))
+            }
+        }
+    }
+
+    /// Moves past `n` bytes from `pos`, if `data` holds them.
+    fn skip(&mut self, data: &[u8], pos: usize, n: usize) -> Option<()> {
+        let end = pos.checked_add(n).filter(|&end| end <= data.len())?;
+        self.pos = end;
+        Some(())
+    }
+
+    /// Scans the next field, list element or end marker, returning `None` if `data` ends
+    /// before it does. The progress is only updated once the step is complete.
+    fn step(&mut self, data: &[u8], limits: &DecodeLimits) -> Option<()> {
+        if self.pos > limits.max_size {
+            // The size limit is reported by decoding.
+            self.ready = true;
+            return Some(());
+        }
+        let shapes = match self.stack.last_mut() {
+            None => {
+                self.ready = true;
+                return Some(());
+            }
+            Some(Frame::Messages { remaining: 0, .. })
+            | Some(Frame::BytesList { remaining: 0 }) => {
+                self.stack.pop();
+                return Some(());
+            }
+            Some(Frame::Messages { shapes, remaining }) => {
+                *remaining -= 1;
+                let shapes = *shapes;
+                self.push(shapes(), limits);
+                return Some(());
+            }
+            Some(Frame::BytesList { .. }) => {
+                let (len, pos) = self.len(data, self.pos, limits.max_size)?;
+                self.skip(data, pos, len)?;
+                if let Some(Frame::BytesList { remaining }) = self.stack.last_mut() {
+                    *remaining -= 1;
+                }
+                return Some(());
+            }
+            Some(Frame::Message(shapes)) => *shapes,
+        };
+
+        let header = *data.get(self.pos)?;
+        let (id, flag) = (header & 0x7f, header & 0x80 != 0);
+        let pos = self.pos + 1;
+        if id == 0x7f {
+            self.pos = pos;
+            self.stack.pop();
+            self.ready = self.stack.is_empty();
+            return Some(());
+        }
+        // Unknown fields are reported by decoding.
+        match shapes.get(id as usize).copied().unwrap_or(Shape::Unknown) {
+            Shape::Empty => self.pos = pos,
+            Shape::Fixed { size, flagged } => {
+                self.skip(data, pos, if flag { flagged } else { size })?
+            }
+            Shape::Varint {
+                flagged: Some(size),
+            } if flag => self.skip(data, pos, size)?,
+            Shape::Varint { .. } => self.pos = pos + varint(data.get(pos..)?)?.1,
+            Shape::Bytes => {
+                let (len, pos) = self.len(data, pos, limits.max_size)?;
+                self.skip(data, pos, len)?;
+            }
+            Shape::FixedList { size } => {
+                let (count, pos) = self.len(data, pos, limits.max_list_size)?;
+                match count.checked_mul(size) {
+                    Some(len) => self.skip(data, pos, len)?,
+                    None => self.ready = true,
+                }
+            }
+            Shape::BytesList => {
+                let (count, pos) = self.len(data, pos, limits.max_list_size)?;
+                self.pos = pos;
+                self.stack.push(Frame::BytesList { remaining: count });
+            }
+            Shape::Message(shapes) => {
+                self.pos = pos;
+                self.push(shapes(), limits);
+            }
+            Shape::Messages(shapes) => {
+                let (count, pos) = self.len(data, pos, limits.max_list_size)?;
+                self.pos = pos;
+                self.stack.push(Frame::Messages {
+                    shapes,
+                    remaining: count,
+                });
+            }
+            Shape::Unknown => self.ready = true,
+        }
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+    use crate::Result;
+
+    struct Node;
+
+    impl Message for Node {
+        fn encode<W: Write>(&self, _w: &mut W) -> Result<()> {
+            unimplemented!()
+        }
+
+        fn decode_fields<R: Read>(_r: &mut R, _limits: &DecodeLimits) -> Result<Self> {
+            unimplemented!()
+        }
+
+        fn size(&self) -> usize {
+            unimplemented!()
+        }
+
+        fn shapes() -> Option<&'static [Shape]> {
+            const SHAPES: &[Shape] = &[
+                Shape::Bytes,
+                Shape::Messages(Node::shapes),
+                Shape::FixedList { size: 4 },
+                Shape::Varint { flagged: Some(4) },
+                Shape::BytesList,
+                Shape::Message(Node::shapes),
+            ];
+            Some(SHAPES)
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let data = [
+            0, 2, b'h', b'i', // text
+            1, 2, 0x7f, 3, 0x80, 1, 0x7f, // messages
+            2, 1, 0, 0, 0, 1, // fixed list
+            0x83, 0xff, 0xff, 0xff, 0xff, // flagged fixed size
+            4, 2, 0, 1, b'a', // binaries
+            5, 0x7f, // message
+            0x7f,
+        ];
+        let limits = DecodeLimits::default();
+        let mut scanner = Scanner::default();
+        let mut pos = 0;
+        for i in 0..data.len() {
+            assert!(!scanner.scan::<Node>(&data[..i], &limits), "at {}", i);
+            assert!(scanner.pos >= pos && scanner.pos <= i);
+            pos = scanner.pos;
+        }
+        assert!(scanner.scan::<Node>(&data, &limits));
+        assert_eq!(scanner.pos, data.len());
+
+        // Decoding reports unknown fields and exceeded limits.
+        assert!(Scanner::default().scan::<Node>(&[9], &limits));
+        assert!(Scanner::default().scan::<Node>(
+            &[5, 5, 5],
+            &DecodeLimits {
+                max_depth: 2,
+                ..limits
+            }
+        ));
+        assert!(Scanner::default().scan::<Node>(
+            &[0, 0x80, 0x80, 0x04],
+            &DecodeLimits {
+                max_size: 1 << 14,
+                ..limits
+            }
+        ));
+    }
+}
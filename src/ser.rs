@@ -0,0 +1,662 @@
+use std::io::Write;
+
+use byteorder::{WriteBytesExt, BE};
+use serde::ser::{self, Impossible, Serialize};
+
+use crate::datetime::SERDE_NAME;
+use crate::types::{write_bytes, write_uint, Element};
+use crate::{write_end, DateTime, Error, ErrorKind, Result, Type};
+
+/// Serializes `value` in the colfer wire format to writer `W`.
+///
+/// The value must be a struct, which is encoded as a message whose field indexes follow the
+/// declaration order. Zero values are omitted as in generated code, `Option::None` included.
+pub fn to_writer<W: Write, T: Serialize + ?Sized>(w: &mut W, value: &T) -> Result<()> {
+    value.serialize(Serializer { w })
+}
+
+/// Serializes `value` in the colfer wire format to `Vec<u8>`.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    to_writer(&mut data, value)?;
+    Ok(data)
+}
+
+#[inline]
+fn unsupported<T>(what: &'static str) -> Result<T> {
+    Err(ErrorKind::Unsupported(what).into())
+}
+
+/// Implements the non-generic `Serializer` methods that are not supported in a context.
+macro_rules! unsupported {
+    ($what:literal; $($method:ident($($ty:ty),*);)*) => {
+        $(
+        fn $method(self, $(_: $ty),*) -> Result<Self::Ok> {
+            unsupported($what)
+        }
+        )*
+    };
+}
+
+/// Implements the tuple struct, map and variant `Serializer` methods, which are never supported.
+macro_rules! unsupported_compound {
+    ($what:literal) => {
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _value: &T,
+        ) -> Result<Self::Ok> {
+            unsupported($what)
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct> {
+            unsupported($what)
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant> {
+            unsupported($what)
+        }
+
+        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+            unsupported($what)
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant> {
+            unsupported($what)
+        }
+    };
+}
+
+/// Serializes a top-level message.
+struct Serializer<'w, W> {
+    w: &'w mut W,
+}
+
+impl<'w, W: Write> ser::Serializer for Serializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = StructSerializer<'w, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "a top-level value other than a struct";
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    unsupported_compound!("a top-level value other than a struct");
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        unsupported("a top-level value other than a struct")
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<()> {
+        unsupported("a top-level value other than a struct")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        unsupported("a top-level value other than a struct")
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(StructSerializer {
+            w: self.w,
+            name,
+            index: 0,
+        })
+    }
+}
+
+/// Serializes the fields of a message, followed by the end marker.
+struct StructSerializer<'w, W> {
+    w: &'w mut W,
+    name: &'static str,
+    index: usize,
+}
+
+impl<'w, W: Write> StructSerializer<'w, W> {
+    fn next_id(&mut self) -> Result<u8> {
+        if self.index >= 0x7f {
+            return unsupported("a struct with more than 127 fields");
+        }
+        self.index += 1;
+        Ok(self.index as u8 - 1)
+    }
+}
+
+impl<'w, W: Write> ser::SerializeStruct for StructSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let id = self.next_id()?;
+        value
+            .serialize(FieldSerializer { w: self.w, id })
+            .map_err(|err| err.with_field(self.name, key))
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<()> {
+        self.next_id().map(|_| ())
+    }
+
+    fn end(self) -> Result<()> {
+        write_end(self.w)
+    }
+}
+
+/// Serializes the value of field `id`.
+struct FieldSerializer<'w, W> {
+    w: &'w mut W,
+    id: u8,
+}
+
+impl<'w, W: Write> ser::Serializer for FieldSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'w, W>;
+    type SerializeTuple = OpaqueSerializer<'w, W>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = FieldStructSerializer<'w, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        (v as i32).encode(self.w, self.id)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        (v as i32).encode(self.w, self.id)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        v.encode(self.w, self.id)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        // The zero char is omitted, so that it survives a roundtrip as an absent field.
+        if v == '\0' {
+            return Ok(());
+        }
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        write_bytes(self.w, self.id, v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        write_bytes(self.w, self.id, v)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        variant_index.encode(self.w, self.id)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    unsupported_compound!("a tuple, map or enum variant with data");
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        Ok(OpaqueSerializer {
+            w: self.w,
+            id: self.id,
+            buf: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SeqSerializer {
+            w: self.w,
+            id: self.id,
+            buf: Vec::new(),
+            len: 0,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        if name == SERDE_NAME {
+            return Ok(FieldStructSerializer::DateTime {
+                w: self.w,
+                id: self.id,
+                value: DateTime::default(),
+            });
+        }
+        self.w.write_u8(self.id)?;
+        Ok(FieldStructSerializer::Message(StructSerializer {
+            w: self.w,
+            name,
+            index: 0,
+        }))
+    }
+}
+
+/// Serializes a struct field, which is either a nested message or a timestamp.
+enum FieldStructSerializer<'w, W> {
+    Message(StructSerializer<'w, W>),
+    DateTime {
+        w: &'w mut W,
+        id: u8,
+        value: DateTime,
+    },
+}
+
+impl<'w, W: Write> ser::SerializeStruct for FieldStructSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        match self {
+            FieldStructSerializer::Message(s) => s.serialize_field(key, value),
+            FieldStructSerializer::DateTime { value: t, .. } => {
+                match (key, value.serialize(ScalarSerializer)?) {
+                    ("seconds", Scalar::I64(seconds)) => t.seconds = seconds,
+                    ("nano_seconds", Scalar::U32(nano_seconds)) => t.nano_seconds = nano_seconds,
+                    _ => return unsupported("a malformed timestamp"),
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        match self {
+            FieldStructSerializer::Message(s) => ser::SerializeStruct::skip_field(s, key),
+            FieldStructSerializer::DateTime { .. } => Ok(()),
+        }
+    }
+
+    fn end(self) -> Result<()> {
+        match self {
+            FieldStructSerializer::Message(s) => ser::SerializeStruct::end(s),
+            FieldStructSerializer::DateTime { w, id, value } => value.encode(w, id),
+        }
+    }
+}
+
+/// Serializes a byte array, which is written as opaque data unless all bytes are zero.
+struct OpaqueSerializer<'w, W> {
+    w: &'w mut W,
+    id: u8,
+    buf: Vec<u8>,
+}
+
+impl<'w, W: Write> ser::SerializeTuple for OpaqueSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        match value.serialize(ScalarSerializer)? {
+            Scalar::U8(v) => self.buf.push(v),
+            _ => return unsupported("a tuple other than a byte array"),
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        if self.buf.iter().any(|b| *b != 0) {
+            self.w.write_u8(self.id)?;
+            self.w.write_all(&self.buf)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a list, which is buffered because its length is written first.
+struct SeqSerializer<'w, W> {
+    w: &'w mut W,
+    id: u8,
+    buf: Vec<u8>,
+    len: u64,
+}
+
+impl<'w, W: Write> ser::SerializeSeq for SeqSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(ElementSerializer { w: &mut self.buf })?;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        if self.len > 0 {
+            self.w.write_u8(self.id)?;
+            write_uint(self.w, self.len)?;
+            self.w.write_all(&self.buf)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes a list element.
+///
+/// A list of `u8` is encoded as binary, so that `Vec<u8>` fields match the generated code.
+struct ElementSerializer<'w, W> {
+    w: &'w mut W,
+}
+
+impl<'w, W: Write> ser::Serializer for ElementSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = ByteSeqSerializer<'w, W>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = StructSerializer<'w, W>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    unsupported! {
+        "a list of this type";
+        serialize_bool(bool);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        Ok(self.w.write_u8(v)?)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        v.write_element(self.w)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        Ok(self.w.write_u32::<BE>(v.to_bits())?)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        Ok(self.w.write_u64::<BE>(v.to_bits())?)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        write_uint(self.w, v.len() as u64)?;
+        Ok(self.w.write_all(v)?)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<()> {
+        unsupported("a list of this type")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    unsupported_compound!("a list of this type");
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        unsupported("a list of this type")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(ByteSeqSerializer {
+            w: self.w,
+            buf: Vec::new(),
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(StructSerializer {
+            w: self.w,
+            name,
+            index: 0,
+        })
+    }
+}
+
+/// Serializes a list of `u8` in a list, which is encoded as binary.
+struct ByteSeqSerializer<'w, W> {
+    w: &'w mut W,
+    buf: Vec<u8>,
+}
+
+impl<'w, W: Write> ser::SerializeSeq for ByteSeqSerializer<'w, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        match value.serialize(ScalarSerializer)? {
+            Scalar::U8(v) => self.buf.push(v),
+            _ => return unsupported("a list of this type"),
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<()> {
+        write_uint(self.w, self.buf.len() as u64)?;
+        Ok(self.w.write_all(&self.buf)?)
+    }
+}
+
+/// An integer captured by [`ScalarSerializer`].
+enum Scalar {
+    U8(u8),
+    U32(u32),
+    I64(i64),
+}
+
+/// Captures the integers that make up timestamps and binaries in lists.
+struct ScalarSerializer;
+
+impl ser::Serializer for ScalarSerializer {
+    type Ok = Scalar;
+    type Error = Error;
+    type SerializeSeq = Impossible<Scalar, Error>;
+    type SerializeTuple = Impossible<Scalar, Error>;
+    type SerializeTupleStruct = Impossible<Scalar, Error>;
+    type SerializeTupleVariant = Impossible<Scalar, Error>;
+    type SerializeMap = Impossible<Scalar, Error>;
+    type SerializeStruct = Impossible<Scalar, Error>;
+    type SerializeStructVariant = Impossible<Scalar, Error>;
+
+    unsupported! {
+        "a value of this type";
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_u16(u16);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Scalar> {
+        Ok(Scalar::U8(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Scalar> {
+        Ok(Scalar::U32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Scalar> {
+        Ok(Scalar::I64(v))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<Scalar> {
+        unsupported("a value of this type")
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Scalar> {
+        value.serialize(self)
+    }
+
+    unsupported_compound!("a value of this type");
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        unsupported("a value of this type")
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        unsupported("a value of this type")
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        unsupported("a value of this type")
+    }
+}
@@ -1,13 +1,21 @@
-use std::io::{Error, ErrorKind, Read, Result, Write};
-use std::ops::Deref;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
 
 use byteorder::{ReadBytesExt, WriteBytesExt, BE};
 
-use crate::{DateTime, Message};
+use crate::{DateTime, DecodeLimits, ErrorKind, Message, Result, Shape};
 
+/// The upper limit for the capacity reserved before the elements of a list are decoded, so
+/// that a forged list length can't allocate much more memory than the input occupies.
+pub(crate) const MAX_LIST_PREALLOCATION: usize = 1024;
+
+/// Writes a varint of at most 9 bytes, the last of which holds 8 bits, as `read_uint` reads.
 #[inline]
-fn write_uint<W: Write>(w: &mut W, mut x: u64) -> Result<()> {
-    while x >= 0x80 {
+pub(crate) fn write_uint<W: Write>(w: &mut W, mut x: u64) -> Result<()> {
+    for _ in 0..8 {
+        if x < 0x80 {
+            break;
+        }
         w.write_u8((x | 0x80) as u8)?;
         x >>= 7;
     }
@@ -16,7 +24,7 @@ fn write_uint<W: Write>(w: &mut W, mut x: u64) -> Result<()> {
 }
 
 #[inline]
-fn read_uint<R: Read>(r: &mut R) -> Result<u64> {
+pub(crate) fn read_uint<R: Read>(r: &mut R) -> Result<u64> {
     let mut x = r.read_u8()? as u64;
     if x >= 0x80 {
         x &= 0x7f;
@@ -34,10 +42,31 @@ fn read_uint<R: Read>(r: &mut R) -> Result<u64> {
     Ok(x)
 }
 
+#[inline]
+fn read_bytes<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Vec<u8>> {
+    let l = limits.check_size(read_uint(r)?)?;
+    let mut s = Vec::new();
+    r.take(l as u64).read_to_end(&mut s)?;
+    if s.len() < l {
+        return Err(ErrorKind::Truncated.into());
+    }
+    Ok(s)
+}
+
+#[inline]
+pub(crate) fn write_bytes<W: Write>(w: &mut W, id: u8, data: &[u8]) -> Result<()> {
+    if !data.is_empty() {
+        w.write_u8(id)?;
+        write_uint(w, data.len() as u64)?;
+        w.write_all(data)?;
+    }
+    Ok(())
+}
+
 #[inline]
 fn uint_size(mut x: u64) -> usize {
     let mut l = 1;
-    while x >= 0x80 {
+    while x >= 0x80 && l < 9 {
         x >>= 7;
         l += 1;
     }
@@ -46,14 +75,19 @@ fn uint_size(mut x: u64) -> usize {
 
 #[doc(hidden)]
 pub trait Type: Sized {
+    /// The layout of the encoded value, see [`Shape`].
+    const SHAPE: Shape = Shape::Unknown;
+
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()>;
 
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self>;
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self>;
 
     fn size(&self) -> usize;
 }
 
 impl Type for bool {
+    const SHAPE: Shape = Shape::Empty;
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self {
@@ -63,7 +97,7 @@ impl Type for bool {
     }
 
     #[inline]
-    fn decode<R: Read>(_r: &mut R, _flag: bool) -> Result<Self> {
+    fn decode<R: Read>(_r: &mut R, _flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         Ok(true)
     }
 
@@ -78,6 +112,8 @@ impl Type for bool {
 }
 
 impl Type for u32 {
+    const SHAPE: Shape = Shape::Varint { flagged: Some(4) };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self >= 1 << 21 {
@@ -91,11 +127,11 @@ impl Type for u32 {
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         if !flag {
-            Ok(read_uint(r)? as u32)
+            u32::try_from(read_uint(r)?).map_err(|_| ErrorKind::IntegerOverflow.into())
         } else {
-            r.read_u32::<BE>()
+            Ok(r.read_u32::<BE>()?)
         }
     }
 
@@ -112,6 +148,8 @@ impl Type for u32 {
 }
 
 impl Type for u64 {
+    const SHAPE: Shape = Shape::Varint { flagged: Some(8) };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self >= 1 << 49 {
@@ -119,17 +157,17 @@ impl Type for u64 {
             w.write_u64::<BE>(*self)?;
         } else if *self != 0 {
             w.write_u8(id)?;
-            write_uint(w, *self as u64)?;
+            write_uint(w, *self)?;
         }
         Ok(())
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         if !flag {
             read_uint(r)
         } else {
-            r.read_u64::<BE>()
+            Ok(r.read_u64::<BE>()?)
         }
     }
 
@@ -137,21 +175,25 @@ impl Type for u64 {
     fn size(&self) -> usize {
         if *self >= 1 << 49 {
             9
-        } else {
+        } else if *self != 0 {
             1 + uint_size(*self)
+        } else {
+            0
         }
     }
 }
 
 impl Type for i32 {
+    const SHAPE: Shape = Shape::Varint { flagged: None };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         (*self as i64).encode(w, id)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
-        Ok(i64::decode(r, flag)? as i32)
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        i32::try_from(i64::decode(r, flag, limits)?).map_err(|_| ErrorKind::IntegerOverflow.into())
     }
 
     #[inline]
@@ -161,6 +203,8 @@ impl Type for i32 {
 }
 
 impl Type for i64 {
+    const SHAPE: Shape = Shape::Varint { flagged: None };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self != 0 {
@@ -171,31 +215,39 @@ impl Type for i64 {
                 x = !x + 1;
                 w.write_u8(id | 0x80)?;
             }
-            write_uint(w, x as u64)?;
+            write_uint(w, x)?;
         }
         Ok(())
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, flag: bool, _limits: &DecodeLimits) -> Result<Self> {
+        let x = read_uint(r)?;
         if !flag {
-            Ok(read_uint(r)? as i64)
+            i64::try_from(x).map_err(|_| ErrorKind::IntegerOverflow.into())
+        } else if x <= 1 << 63 {
+            Ok((x as i64).wrapping_neg())
         } else {
-            Ok((!read_uint(r)? + 1) as i64)
+            Err(ErrorKind::IntegerOverflow.into())
         }
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if *self >= 0 {
-            1 + uint_size(*self as u64)
+        if *self != 0 {
+            1 + uint_size(self.unsigned_abs())
         } else {
-            1 + uint_size((!*self + 1) as u64)
+            0
         }
     }
 }
 
 impl Type for f32 {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 4,
+        flagged: 4,
+    };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self != 0.0 {
@@ -206,7 +258,7 @@ impl Type for f32 {
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, _flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         Ok(f32::from_bits(r.read_u32::<BE>()?))
     }
 
@@ -221,6 +273,11 @@ impl Type for f32 {
 }
 
 impl Type for f64 {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 8,
+        flagged: 8,
+    };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         if *self != 0.0 {
@@ -231,7 +288,7 @@ impl Type for f64 {
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, _flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         Ok(f64::from_bits(r.read_u64::<BE>()?))
     }
 
@@ -246,6 +303,11 @@ impl Type for f64 {
 }
 
 impl Type for DateTime {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 8,
+        flagged: 12,
+    };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
         let DateTime {
@@ -253,7 +315,8 @@ impl Type for DateTime {
             nano_seconds: ns,
         } = *self;
         if s != 0 || ns != 0 {
-            if s < 1 << 32 {
+            // Negative seconds need the 64-bit encoding, as the 32-bit one is unsigned.
+            if (0..1 << 32).contains(&s) {
                 w.write_u8(id)?;
                 w.write_u32::<BE>(s as u32)?;
             } else {
@@ -266,7 +329,7 @@ impl Type for DateTime {
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
+    fn decode<R: Read>(r: &mut R, flag: bool, _limits: &DecodeLimits) -> Result<Self> {
         if !flag {
             let s = r.read_u32::<BE>()?;
             let ns = r.read_u32::<BE>()?;
@@ -291,7 +354,7 @@ impl Type for DateTime {
             nano_seconds: ns,
         } = *self;
         if s != 0 || ns != 0 {
-            if s < 1 << 32 {
+            if (0..1 << 32).contains(&s) {
                 1 + 8
             } else {
                 1 + 12
@@ -303,22 +366,17 @@ impl Type for DateTime {
 }
 
 impl Type for String {
+    const SHAPE: Shape = Shape::Bytes;
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
-            w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            w.write_all(self.as_bytes())?;
-        }
-        Ok(())
+        write_bytes(w, id, self.as_bytes())
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = vec![0; l as usize];
-        r.read_exact(&mut s)?;
-        Ok(String::from_utf8(s).map_err(|err| Error::new(ErrorKind::Other, err))?)
+    fn decode<R: Read>(r: &mut R, _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        let s = read_bytes(r, limits)?;
+        String::from_utf8(s).map_err(|err| err.utf8_error().into())
     }
 
     #[inline]
@@ -332,22 +390,16 @@ impl Type for String {
 }
 
 impl Type for Vec<u8> {
+    const SHAPE: Shape = Shape::Bytes;
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
-            w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            w.write_all(self)?;
-        }
-        Ok(())
+        write_bytes(w, id, self)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = vec![0; l as usize];
-        r.read_exact(&mut s)?;
-        Ok(s)
+    fn decode<R: Read>(r: &mut R, _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        read_bytes(r, limits)
     }
 
     #[inline]
@@ -372,18 +424,38 @@ pub fn encode_message<W: Write, T: Message>(w: &mut W, id: u8, message: Option<&
 
 #[doc(hidden)]
 #[inline]
-pub fn decode_message<R: Read, M: Message, T: Deref<Target = M> + From<M>>(
-    r: &mut R,
-) -> Result<Option<T>> {
-    Ok(Some(T::from(M::decode(r)?)))
+pub fn decode_message<R: Read, T: Message>(r: &mut R, limits: &DecodeLimits) -> Result<T> {
+    T::decode_fields(r, &limits.nested()?)
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn message_size<T: Message>(message: Option<&T>) -> usize {
+    message
+        .map(|message| 1 + message.size())
+        .unwrap_or_default()
+}
+
+/// Writes the header and length of a list field.
+#[doc(hidden)]
+#[inline]
+pub fn encode_list_header<W: Write>(w: &mut W, id: u8, len: usize) -> Result<()> {
+    w.write_u8(id)?;
+    write_uint(w, len as u64)
+}
+
+/// Reads the length of a list field, failing if it exceeds `limits`.
+#[doc(hidden)]
+#[inline]
+pub fn decode_list_len<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<usize> {
+    limits.check_list_size(read_uint(r)?)
 }
 
 #[doc(hidden)]
 #[inline]
 pub fn encode_messages<W: Write, T: Message>(w: &mut W, id: u8, messages: &[T]) -> Result<()> {
     if !messages.is_empty() {
-        w.write_u8(id)?;
-        write_uint(w, messages.len() as u64)?;
+        encode_list_header(w, id, messages.len())?;
         for s in messages {
             s.encode(w)?;
         }
@@ -393,227 +465,356 @@ pub fn encode_messages<W: Write, T: Message>(w: &mut W, id: u8, messages: &[T])
 
 #[doc(hidden)]
 #[inline]
-pub fn decode_messages<R: Read, T: Message>(r: &mut R) -> Result<Vec<T>> {
-    let l = read_uint(r)?;
-    let mut s = Vec::with_capacity(l as usize);
+pub fn messages_size<T: Message>(messages: &[T]) -> usize {
+    if !messages.is_empty() {
+        1 + uint_size(messages.len() as u64) + messages.iter().map(|s| s.size()).sum::<usize>()
+    } else {
+        0
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn decode_messages<R: Read, T: Message>(r: &mut R, limits: &DecodeLimits) -> Result<Vec<T>> {
+    let l = decode_list_len(r, limits)?;
+    let nested = limits.nested()?;
+    let mut s = Vec::with_capacity(l.min(MAX_LIST_PREALLOCATION));
     for _ in 0..l {
-        s.push(T::decode(r)?);
+        s.push(T::decode_fields(r, &nested)?);
     }
     Ok(s)
 }
 
-impl Type for Vec<String> {
+impl Type for u8 {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 1,
+        flagged: 1,
+    };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
+        if *self != 0 {
             w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            for s in self {
-                write_uint(w, self.len() as u64)?;
-                w.write_all(s.as_bytes())?;
-            }
+            w.write_u8(*self)?;
         }
         Ok(())
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = Vec::with_capacity(l as usize);
-        for _ in 0..l {
-            let sz = read_uint(r)?;
-            let mut d = vec![0; sz as usize];
-            r.read_exact(&mut d)?;
-            s.push(String::from_utf8(d).map_err(|err| Error::new(ErrorKind::Other, err))?);
-        }
-        Ok(s)
+    fn decode<R: Read>(r: &mut R, _flag: bool, _limits: &DecodeLimits) -> Result<Self> {
+        Ok(r.read_u8()?)
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if !self.is_empty() {
-            1 + uint_size(self.len() as u64)
-                + self
-                    .iter()
-                    .map(|s| uint_size(s.len() as u64) + s.len())
-                    .sum::<usize>()
+        if *self != 0 {
+            1 + 1
         } else {
             0
         }
     }
 }
 
-impl Type for Vec<Vec<u8>> {
+impl Type for u16 {
+    const SHAPE: Shape = Shape::Fixed {
+        size: 2,
+        flagged: 1,
+    };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
+        if *self >= 1 << 8 {
             w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            for s in self {
-                write_uint(w, self.len() as u64)?;
-                w.write_all(s)?;
-            }
+            w.write_u16::<BE>(*self)?;
+        } else if *self != 0 {
+            w.write_u8(id | 0x80)?;
+            w.write_u8(*self as u8)?;
         }
         Ok(())
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = Vec::with_capacity(l as usize);
-        for _ in 0..l {
-            let sz = read_uint(r)?;
-            let mut d = vec![0; sz as usize];
-            r.read_exact(&mut d)?;
-            s.push(d);
+    fn decode<R: Read>(r: &mut R, flag: bool, _limits: &DecodeLimits) -> Result<Self> {
+        if !flag {
+            Ok(r.read_u16::<BE>()?)
+        } else {
+            Ok(r.read_u8()? as u16)
         }
-        Ok(s)
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if !self.is_empty() {
-            1 + uint_size(self.len() as u64)
-                + self
-                    .iter()
-                    .map(|s| uint_size(s.len() as u64) + s.len())
-                    .sum::<usize>()
+        if *self >= 1 << 8 {
+            3
+        } else if *self != 0 {
+            2
         } else {
             0
         }
     }
 }
 
-impl Type for u8 {
+// `int8` and `int16` are extensions of colfer-rs, encoded like `int32`.
+impl Type for i8 {
+    const SHAPE: Shape = Shape::Varint { flagged: None };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if *self != 0 {
-            w.write_u8(id)?;
-            w.write_u8(*self)?;
-        }
-        Ok(())
+        (*self as i64).encode(w, id)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        r.read_u8()
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        i8::try_from(i64::decode(r, flag, limits)?).map_err(|_| ErrorKind::IntegerOverflow.into())
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if *self != 0 {
-            1 + 1
-        } else {
-            0
-        }
+        (*self as i64).size()
     }
 }
 
-impl Type for u16 {
+impl Type for i16 {
+    const SHAPE: Shape = Shape::Varint { flagged: None };
+
     #[inline]
     fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if *self >= 1 << 8 {
-            w.write_u8(id)?;
-            w.write_u16::<BE>(*self)?;
-        } else if *self != 0 {
-            w.write_u8(id | 0x80)?;
-            w.write_u8(*self as u8)?;
-        }
-        Ok(())
+        (*self as i64).encode(w, id)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, flag: bool) -> Result<Self> {
-        if !flag {
-            r.read_u16::<BE>()
-        } else {
-            Ok(r.read_u8()? as u16)
-        }
+    fn decode<R: Read>(r: &mut R, flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        i16::try_from(i64::decode(r, flag, limits)?).map_err(|_| ErrorKind::IntegerOverflow.into())
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if *self >= 1 << 8 {
-            3
-        } else if *self != 0 {
-            2
-        } else {
-            0
-        }
+        (*self as i64).size()
     }
 }
 
-impl Type for Vec<f32> {
+#[inline]
+pub(crate) fn write_element_bytes<W: Write>(w: &mut W, data: &[u8]) -> Result<()> {
+    write_uint(w, data.len() as u64)?;
+    Ok(w.write_all(data)?)
+}
+
+#[inline]
+pub(crate) fn element_bytes_size(data: &[u8]) -> usize {
+    uint_size(data.len() as u64) + data.len()
+}
+
+/// The element type of a list field.
+#[doc(hidden)]
+pub trait Element: Sized {
+    /// The layout of a list of elements, see [`Shape`].
+    const LIST_SHAPE: Shape = Shape::Unknown;
+
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()>;
+
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self>;
+
+    fn element_size(&self) -> usize;
+}
+
+impl Element for f32 {
+    const LIST_SHAPE: Shape = Shape::FixedList { size: 4 };
+
     #[inline]
-    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
-            w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            for s in self {
-                w.write_u32::<BE>(s.to_bits())?;
-            }
-        }
-        Ok(())
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        Ok(w.write_u32::<BE>(self.to_bits())?)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = Vec::with_capacity(l as usize);
-        for _ in 0..l {
-            s.push(f32::from_bits(r.read_u32::<BE>()?));
-        }
-        Ok(s)
+    fn read_element<R: Read>(r: &mut R, _limits: &DecodeLimits) -> Result<Self> {
+        Ok(f32::from_bits(r.read_u32::<BE>()?))
     }
 
     #[inline]
-    fn size(&self) -> usize {
-        if !self.is_empty() {
-            1 + uint_size(self.len() as u64)
-                + self
-                    .iter()
-                    .map(|n| uint_size(n.to_bits() as u64))
-                    .sum::<usize>()
-        } else {
-            0
-        }
+    fn element_size(&self) -> usize {
+        4
     }
 }
 
-impl Type for Vec<f64> {
+impl Element for f64 {
+    const LIST_SHAPE: Shape = Shape::FixedList { size: 8 };
+
     #[inline]
-    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
-        if !self.is_empty() {
-            w.write_u8(id)?;
-            write_uint(w, self.len() as u64)?;
-            for s in self {
-                w.write_u64::<BE>(s.to_bits())?;
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        Ok(w.write_u64::<BE>(self.to_bits())?)
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, _limits: &DecodeLimits) -> Result<Self> {
+        Ok(f64::from_bits(r.read_u64::<BE>()?))
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        8
+    }
+}
+
+impl Element for String {
+    const LIST_SHAPE: Shape = Shape::BytesList;
+
+    #[inline]
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_element_bytes(w, self.as_bytes())
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        let s = read_bytes(r, limits)?;
+        String::from_utf8(s).map_err(|err| err.utf8_error().into())
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        element_bytes_size(self.as_bytes())
+    }
+}
+
+impl Element for Vec<u8> {
+    const LIST_SHAPE: Shape = Shape::BytesList;
+
+    #[inline]
+    fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_element_bytes(w, self)
+    }
+
+    #[inline]
+    fn read_element<R: Read>(r: &mut R, limits: &DecodeLimits) -> Result<Self> {
+        read_bytes(r, limits)
+    }
+
+    #[inline]
+    fn element_size(&self) -> usize {
+        element_bytes_size(self)
+    }
+}
+
+/// Integer lists are an extension of colfer-rs, see the crate documentation. The elements are
+/// written as is, big-endian in two's complement, the way colfer writes floating point lists.
+/// Elements have no header, so unlike single values they have no flag to mark a compact or
+/// negative encoding.
+macro_rules! impl_integer_element {
+    ($($ty:ty),*) => {
+        $(
+        impl Element for $ty {
+            const LIST_SHAPE: Shape = Shape::FixedList {
+                size: std::mem::size_of::<$ty>(),
+            };
+
+            #[inline]
+            fn write_element<W: Write>(&self, w: &mut W) -> Result<()> {
+                Ok(w.write_all(&self.to_be_bytes())?)
+            }
+
+            #[inline]
+            fn read_element<R: Read>(r: &mut R, _limits: &DecodeLimits) -> Result<Self> {
+                let mut data = [0; std::mem::size_of::<$ty>()];
+                r.read_exact(&mut data)?;
+                Ok(<$ty>::from_be_bytes(data))
+            }
+
+            #[inline]
+            fn element_size(&self) -> usize {
+                std::mem::size_of::<$ty>()
             }
         }
-        Ok(())
+        )*
+    };
+}
+
+impl_integer_element!(u16, u32, u64, i8, i16, i32, i64);
+
+#[inline]
+pub(crate) fn encode_list<W: Write, T: Element>(w: &mut W, id: u8, list: &[T]) -> Result<()> {
+    if !list.is_empty() {
+        encode_list_header(w, id, list.len())?;
+        for x in list {
+            x.write_element(w)?;
+        }
+    }
+    Ok(())
+}
+
+#[inline]
+pub(crate) fn list_size<T: Element>(list: &[T]) -> usize {
+    if !list.is_empty() {
+        1 + uint_size(list.len() as u64) + list.iter().map(T::element_size).sum::<usize>()
+    } else {
+        0
+    }
+}
+
+impl<T: Element> Type for Vec<T> {
+    const SHAPE: Shape = T::LIST_SHAPE;
+
+    #[inline]
+    fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+        encode_list(w, id, self)
     }
 
     #[inline]
-    fn decode<R: Read>(r: &mut R, _flag: bool) -> Result<Self> {
-        let l = read_uint(r)?;
-        let mut s = Vec::with_capacity(l as usize);
+    fn decode<R: Read>(r: &mut R, _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        let l = decode_list_len(r, limits)?;
+        let mut s = Vec::with_capacity(l.min(MAX_LIST_PREALLOCATION));
         for _ in 0..l {
-            s.push(f64::from_bits(r.read_u64::<BE>()?));
+            s.push(T::read_element(r, limits)?);
         }
         Ok(s)
     }
 
     #[inline]
     fn size(&self) -> usize {
-        if !self.is_empty() {
-            1 + uint_size(self.len() as u64)
-                + self.iter().map(|n| uint_size(n.to_bits())).sum::<usize>()
-        } else {
-            0
-        }
+        list_size(self)
     }
 }
 
+/// Opaque data of a fixed size, an extension of colfer-rs. It is written as is after the
+/// header, the way colfer writes floating points, and omitted if all bytes are zero.
+macro_rules! impl_opaque {
+    ($($n:literal),*) => {
+        $(
+        impl Type for [u8; $n] {
+            const SHAPE: Shape = Shape::Fixed {
+                size: $n,
+                flagged: $n,
+            };
+
+            #[inline]
+            fn encode<W: Write>(&self, w: &mut W, id: u8) -> Result<()> {
+                if *self != [0; $n] {
+                    w.write_u8(id)?;
+                    w.write_all(self)?;
+                }
+                Ok(())
+            }
+
+            #[inline]
+            fn decode<R: Read>(r: &mut R, _flag: bool, _limits: &DecodeLimits) -> Result<Self> {
+                let mut data = [0; $n];
+                r.read_exact(&mut data)?;
+                Ok(data)
+            }
+
+            #[inline]
+            fn size(&self) -> usize {
+                if *self != [0; $n] {
+                    1 + $n
+                } else {
+                    0
+                }
+            }
+        }
+        )*
+    };
+}
+
+impl_opaque!(1, 2, 4, 8);
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Debug;
@@ -624,6 +825,7 @@ mod tests {
     fn do_test<T: Type + PartialEq + Debug + Default>(value: T) {
         let mut data = Vec::new();
         value.encode(&mut data, 10).unwrap();
+        assert_eq!(value.size(), data.len());
 
         let mut r = Cursor::new(&data);
         if data.is_empty() {
@@ -631,7 +833,11 @@ mod tests {
         } else {
             let (id, flag) = read_header(&mut r).unwrap();
             assert_eq!(id, 10);
-            assert_eq!(T::decode(&mut r, flag).unwrap(), value);
+            assert_eq!(
+                T::decode(&mut r, flag, &DecodeLimits::default()).unwrap(),
+                value
+            );
+            assert_eq!(r.position(), data.len() as u64);
         }
     }
 
@@ -659,4 +865,95 @@ mod tests {
         do_test(f64::MAX);
         do_test(f64::MIN);
     }
+
+    #[test]
+    fn test_lists() {
+        do_test(vec![1.0f32, -2.5]);
+        do_test(vec![1.0f64, -2.5]);
+        do_test(vec!["a".to_string(), "bc".to_string()]);
+        do_test(vec![vec![1u8], vec![2, 3]]);
+        do_test(Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_small_integers() {
+        do_test(i8::MIN);
+        do_test(i8::MAX);
+        do_test(i16::MIN);
+        do_test(-1i16);
+    }
+
+    #[test]
+    fn test_integer_lists() {
+        do_test(vec![0u16, 300, u16::MAX]);
+        do_test(vec![1u32, u32::MAX]);
+        do_test(vec![u64::MAX, 0]);
+        do_test(vec![i8::MIN, -1, i8::MAX]);
+        do_test(vec![i16::MIN, 0, i16::MAX]);
+        do_test(vec![-1i32, 1, i32::MIN]);
+        do_test(vec![i64::MIN, i64::MAX]);
+
+        let mut data = Vec::new();
+        vec![-2i32, 2].encode(&mut data, 10).unwrap();
+        assert_eq!(data, [10, 2, 0xff, 0xff, 0xff, 0xfe, 0, 0, 0, 2]);
+
+        let mut r = Cursor::new([2, 0x80]);
+        let err = Vec::<i8>::decode(&mut r, false, &DecodeLimits::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Truncated));
+    }
+
+    #[test]
+    fn test_opaque() {
+        do_test([0u8; 1]);
+        do_test([1u8, 2]);
+        do_test([0u8, 0, 0, 1]);
+        do_test(u64::MAX.to_be_bytes());
+    }
+
+    #[test]
+    fn test_u64() {
+        do_test(0u64);
+        do_test(1u64 << 40);
+        do_test(u64::MAX);
+    }
+
+    #[test]
+    fn test_integer_overflow() {
+        let mut data = Vec::new();
+        (1u64 << 40).encode(&mut data, 10).unwrap();
+        let mut r = Cursor::new(&data[1..]);
+        let err = u32::decode(&mut r, false, &DecodeLimits::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::IntegerOverflow));
+
+        let mut data = Vec::new();
+        i64::MIN.encode(&mut data, 10).unwrap();
+        let mut r = Cursor::new(&data[1..]);
+        let err = i32::decode(&mut r, true, &DecodeLimits::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::IntegerOverflow));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut r = Cursor::new([2, 0xc3, 0x28]);
+        let err = String::decode(&mut r, false, &DecodeLimits::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidUtf8(_)));
+
+        let mut r = Cursor::new([2, b'a']);
+        let err = String::decode(&mut r, false, &DecodeLimits::default()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Truncated));
+    }
+
+    #[test]
+    fn test_forged_list_len() {
+        let limits = DecodeLimits {
+            max_list_size: usize::MAX,
+            ..DecodeLimits::default()
+        };
+        let mut data = Vec::new();
+        encode_list_header(&mut data, 10, 1 << 60).unwrap();
+        let mut r = Cursor::new(&data);
+        let (_, flag) = read_header(&mut r).unwrap();
+        let err = Vec::<u64>::decode(&mut r, flag, &limits).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Truncated));
+    }
 }
@@ -0,0 +1,288 @@
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use crate::types::{read_uint, Element};
+use crate::{DateTime, DecodeLimits, Error, ErrorKind, LimitError, Result, Type};
+
+/// A colfer message decoded without copying, borrowing text and binary fields from the input.
+pub trait MessageView<'a>: Sized {
+    /// Decodes an instance of the message from `data`.
+    fn from_bytes(data: &'a [u8]) -> Result<Self> {
+        Self::from_bytes_with_limits(data, &DecodeLimits::default())
+    }
+
+    /// Decodes an instance of the message from `data`, failing with
+    /// [`ErrorKind::LimitExceeded`] if the input exceeds `limits`.
+    fn from_bytes_with_limits(data: &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        decode_prefix(&mut { data }, data, limits)
+    }
+
+    /// Decodes the fields of the message, advancing `buf` past them.
+    #[doc(hidden)]
+    fn decode_fields(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self>;
+}
+
+fn decode_prefix<'a, T: MessageView<'a>>(
+    buf: &mut &'a [u8],
+    data: &'a [u8],
+    limits: &DecodeLimits,
+) -> Result<T> {
+    if data.len() > limits.max_size {
+        return Err(LimitError::Size.into());
+    }
+    T::decode_fields(buf, limits).map_err(|err| err.with_offset((data.len() - buf.len()) as u64))
+}
+
+/// Decodes a message view from `data`, failing if any bytes follow it.
+#[doc(hidden)]
+pub fn view_from_bytes_exact<'a, T: MessageView<'a>>(
+    data: &'a [u8],
+    limits: &DecodeLimits,
+) -> Result<T> {
+    let mut buf = data;
+    let message = decode_prefix(&mut buf, data, limits)?;
+    if !buf.is_empty() {
+        return Err(
+            Error::new(ErrorKind::TrailingData).with_offset((data.len() - buf.len()) as u64)
+        );
+    }
+    Ok(message)
+}
+
+#[doc(hidden)]
+pub trait TypeView<'a>: Sized {
+    fn decode_view(buf: &mut &'a [u8], flag: bool, limits: &DecodeLimits) -> Result<Self>;
+}
+
+macro_rules! impl_type_view {
+    ($($ty:ty),*) => {
+        $(
+        impl<'a> TypeView<'a> for $ty {
+            #[inline]
+            fn decode_view(buf: &mut &'a [u8], flag: bool, limits: &DecodeLimits) -> Result<Self> {
+                Type::decode(buf, flag, limits)
+            }
+        }
+        )*
+    };
+}
+
+impl_type_view!(
+    bool, u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, DateTime, [u8; 1], [u8; 2], [u8; 4],
+    [u8; 8]
+);
+
+#[inline]
+fn read_slice<'a>(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<&'a [u8]> {
+    let l = limits.check_size(read_uint(buf)?)?;
+    if buf.len() < l {
+        return Err(ErrorKind::Truncated.into());
+    }
+    let (s, rest) = buf.split_at(l);
+    *buf = rest;
+    Ok(s)
+}
+
+impl<'a> TypeView<'a> for &'a str {
+    #[inline]
+    fn decode_view(buf: &mut &'a [u8], _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        Ok(std::str::from_utf8(read_slice(buf, limits)?)?)
+    }
+}
+
+impl<'a> TypeView<'a> for &'a [u8] {
+    #[inline]
+    fn decode_view(buf: &mut &'a [u8], _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        read_slice(buf, limits)
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn decode_message_view<'a, T: MessageView<'a>>(
+    buf: &mut &'a [u8],
+    limits: &DecodeLimits,
+) -> Result<T> {
+    T::decode_fields(buf, &limits.nested()?)
+}
+
+/// The element type of a [`ListView`].
+#[doc(hidden)]
+pub trait ElementView<'a>: Sized {
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self>;
+}
+
+impl<'a> ElementView<'a> for f32 {
+    #[inline]
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        f32::decode_view(buf, false, limits)
+    }
+}
+
+impl<'a> ElementView<'a> for f64 {
+    #[inline]
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        f64::decode_view(buf, false, limits)
+    }
+}
+
+macro_rules! impl_integer_element_view {
+    ($($ty:ty),*) => {
+        $(
+        impl<'a> ElementView<'a> for $ty {
+            #[inline]
+            fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+                Element::read_element(buf, limits)
+            }
+        }
+        )*
+    };
+}
+
+impl_integer_element_view!(u16, u32, u64, i8, i16, i32, i64);
+
+impl<'a> ElementView<'a> for &'a str {
+    #[inline]
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        <&str>::decode_view(buf, false, limits)
+    }
+}
+
+impl<'a> ElementView<'a> for &'a [u8] {
+    #[inline]
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        <&[u8]>::decode_view(buf, false, limits)
+    }
+}
+
+impl<'a, T: MessageView<'a>> ElementView<'a> for T {
+    #[inline]
+    fn decode_element(buf: &mut &'a [u8], limits: &DecodeLimits) -> Result<Self> {
+        decode_message_view(buf, limits)
+    }
+}
+
+/// A list borrowed from the input, whose elements are decoded lazily while iterating.
+///
+/// All elements are validated when the list is decoded, so iterating never fails.
+pub struct ListView<'a, T> {
+    data: &'a [u8],
+    len: usize,
+    limits: DecodeLimits,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> ListView<'a, T> {
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the list contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator over the elements of the list.
+    pub fn iter(&self) -> ListViewIter<'a, T> {
+        ListViewIter {
+            data: self.data,
+            remaining: self.len,
+            limits: self.limits,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Default for ListView<'a, T> {
+    fn default() -> Self {
+        Self {
+            data: &[],
+            len: 0,
+            limits: DecodeLimits::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Clone for ListView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for ListView<'a, T> {}
+
+impl<'a, T: ElementView<'a> + Debug> Debug for ListView<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T: ElementView<'a> + PartialEq> PartialEq for ListView<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<'a, T: ElementView<'a>> TypeView<'a> for ListView<'a, T> {
+    #[inline]
+    fn decode_view(buf: &mut &'a [u8], _flag: bool, limits: &DecodeLimits) -> Result<Self> {
+        let len = limits.check_list_size(read_uint(buf)?)?;
+        let data = *buf;
+        for _ in 0..len {
+            T::decode_element(buf, limits)?;
+        }
+        Ok(Self {
+            data: &data[..data.len() - buf.len()],
+            len,
+            limits: *limits,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T: ElementView<'a>> IntoIterator for ListView<'a, T> {
+    type Item = T;
+    type IntoIter = ListViewIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: ElementView<'a>> IntoIterator for &ListView<'a, T> {
+    type Item = T;
+    type IntoIter = ListViewIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the elements of a [`ListView`].
+pub struct ListViewIter<'a, T> {
+    data: &'a [u8],
+    remaining: usize,
+    limits: DecodeLimits,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: ElementView<'a>> Iterator for ListViewIter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // The elements have been validated by `ListView::decode_view`.
+        T::decode_element(&mut self.data, &self.limits).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: ElementView<'a>> ExactSizeIterator for ListViewIter<'a, T> {}